@@ -0,0 +1,111 @@
+//! Compares the binary-heap frontier used by `PathFinder::dijkstra` against a frontier that is
+//! scanned linearly for its closest node on every step, on a generated graph with 100k nodes.
+//!
+//! Run with `$ cargo bench --bench frontier`.
+#![allow(clippy::needless_return)]
+
+use rust_dijkstra::{Edge, Graph, GraphNode, PathFinder, Weight};
+
+// the generator the tests use, to keep the graph the same between runs
+#[allow(dead_code)]
+#[path = "../src/random_graph.rs"]
+mod random_graph;
+
+use crate::random_graph::RandomGraphs;
+
+use std::time::{Duration, Instant};
+
+const GRID_WIDTH: usize = 400;
+const GRID_HEIGHT: usize = 250;
+
+/// a grid of GRID_WIDTH x GRID_HEIGHT nodes, each connected to its right and lower neighbours
+/// with pseudo-random weights, so that the frontier is a wide wavefront rather than a single path
+fn generate_grid_graph() -> Graph {
+    let number_of_nodes = GRID_WIDTH * GRID_HEIGHT;
+    let graph_nodes = (0..number_of_nodes)
+        .map(|idx| GraphNode::new(idx, format!("n{}", idx)))
+        .collect();
+
+    let mut random = RandomGraphs::new(42);
+    let mut next_weight = || 1 + random.next(100);
+
+    let mut edges = Vec::with_capacity(2 * number_of_nodes);
+    for row in 0..GRID_HEIGHT {
+        for col in 0..GRID_WIDTH {
+            let idx = row * GRID_WIDTH + col;
+            if col + 1 < GRID_WIDTH {
+                edges.push(Edge::new(idx, idx + 1, next_weight()));
+            }
+            if row + 1 < GRID_HEIGHT {
+                edges.push(Edge::new(idx, idx + GRID_WIDTH, next_weight()));
+            }
+        }
+    }
+    return Graph::new(graph_nodes, edges).unwrap();
+}
+
+/// dijkstra where the frontier is an unsorted Vec, scanned in full to find the closest node
+fn dijkstra_linear_frontier(graph: &Graph, start_idx: usize, end_idx: usize) -> usize {
+    let mut dist_to_node = vec![usize::MAX; graph.number_of_nodes()];
+    let mut frontier: Vec<usize> = vec![start_idx];
+    dist_to_node[start_idx] = 0;
+
+    while !frontier.is_empty() {
+        let mut pos_closest = 0;
+        for (pos, &node_idx) in frontier.iter().enumerate() {
+            if dist_to_node[node_idx] < dist_to_node[frontier[pos_closest]] {
+                pos_closest = pos;
+            }
+        }
+        let current_idx = frontier.swap_remove(pos_closest);
+        if current_idx == end_idx {
+            return dist_to_node[end_idx];
+        }
+        for edge in graph.connections(current_idx) {
+            let dist_through_current = dist_to_node[current_idx] + edge.weight;
+            if dist_through_current < dist_to_node[edge.index_second] {
+                if !frontier.contains(&edge.index_second) {
+                    frontier.push(edge.index_second);
+                }
+                dist_to_node[edge.index_second] = dist_through_current;
+            }
+        }
+    }
+    panic!("generated graph should be connected");
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    return (result, start.elapsed());
+}
+
+fn main() {
+    let graph = generate_grid_graph();
+    let number_of_nodes = graph.number_of_nodes();
+    // opposite corners, so that every node is settled before the end is reached
+    let route = (0, number_of_nodes - 1);
+    let mut pf = PathFinder::new(graph, vec![route]).unwrap();
+
+    let ((heap_dist, _), heap_time) = time(|| pf.dijkstra().unwrap());
+    let (linear_dist, linear_time) =
+        time(|| dijkstra_linear_frontier(pf.graph(), route.0, route.1));
+    assert_eq!(heap_dist, linear_dist);
+    let ((bidirectional_dist, _), bidirectional_time) =
+        time(|| pf.bidirectional_dijkstra().unwrap());
+    assert_eq!(heap_dist, bidirectional_dist);
+
+    println!(
+        "{} nodes, {} edges, dist {}",
+        number_of_nodes,
+        pf.graph().edges().len(),
+        heap_dist
+    );
+    println!("binary heap frontier:  {:>10.2?}", heap_time);
+    println!("linear scan frontier:  {:>10.2?}", linear_time);
+    println!("bidirectional search:  {:>10.2?}", bidirectional_time);
+    println!(
+        "speed up:              {:>9.1}x",
+        linear_time.as_secs_f64() / heap_time.as_secs_f64()
+    );
+}
@@ -0,0 +1,402 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::parse_input::Edge;
+use crate::search_context::SearchContext;
+use crate::weight::Weight;
+
+/// The shortest distance between every pair of nodes, with the next hop of each shortest route
+/// so that any of the routes can be rebuilt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllPairs<W = usize> {
+    /// `distances[from][to]`, or None if `to` can't be reached from `from`
+    pub distances: Vec<Vec<Option<W>>>,
+    /// `next_hops[from][to]` is the node after `from` on the shortest route to `to`, and
+    /// `next_hops[node_idx][node_idx]` is the node itself
+    pub next_hops: Vec<Vec<Option<usize>>>,
+}
+
+impl<W: Weight> AllPairs<W> {
+    /// choose between the algorithms: Floyd-Warshall for dense graphs, where its O(V^3) is no
+    /// worse than running dijkstra from every node, otherwise repeated_dijkstra, or johnson if
+    /// there are negative weights
+    pub fn new(graph: &Graph<W>) -> Result<AllPairs<W>, DijkstraError> {
+        if is_dense(graph) {
+            return AllPairs::floyd_warshall(graph);
+        }
+        if has_negative_weights(graph) {
+            return AllPairs::johnson(graph);
+        }
+        return Ok(AllPairs::repeated_dijkstra(graph));
+    }
+
+    /// relax every pair of nodes through each node in turn. Negative weights are allowed, but a
+    /// negative cycle is an error.
+    pub fn floyd_warshall(graph: &Graph<W>) -> Result<AllPairs<W>, DijkstraError> {
+        let number_of_nodes = graph.number_of_nodes;
+        let mut all_pairs = AllPairs::unreachable(number_of_nodes);
+        for (from_idx, edges) in graph.connections.iter().enumerate() {
+            for edge in edges {
+                all_pairs.distances[from_idx][edge.index_second] = Some(edge.weight);
+                all_pairs.next_hops[from_idx][edge.index_second] = Some(edge.index_second);
+            }
+        }
+
+        for through_idx in 0..number_of_nodes {
+            for from_idx in 0..number_of_nodes {
+                let dist_to_through = match all_pairs.distances[from_idx][through_idx] {
+                    Some(dist) => dist,
+                    None => continue,
+                };
+                for to_idx in 0..number_of_nodes {
+                    let dist_from_through = match all_pairs.distances[through_idx][to_idx] {
+                        Some(dist) => dist,
+                        None => continue,
+                    };
+                    // paths whose distance overflows are dropped, as in dijkstra
+                    let dist_via_through = match dist_to_through.checked_add(dist_from_through) {
+                        Some(dist) => dist,
+                        None => continue,
+                    };
+                    if let Some(dist) = all_pairs.distances[from_idx][to_idx] {
+                        if dist <= dist_via_through {
+                            continue;
+                        }
+                    }
+                    all_pairs.distances[from_idx][to_idx] = Some(dist_via_through);
+                    all_pairs.next_hops[from_idx][to_idx] =
+                        all_pairs.next_hops[from_idx][through_idx];
+                }
+            }
+            // a node that can get back to itself with a negative distance is on a negative cycle
+            if let Some(node_idx) = (0..number_of_nodes)
+                .find(|&idx| all_pairs.distances[idx][idx].is_some_and(|dist| dist < W::zero()))
+            {
+                let mut context = SearchContext::new(number_of_nodes);
+                return Err(context
+                    .bellman_ford_from(graph, node_idx)
+                    .expect_err("Expected a negative cycle through the node"));
+            }
+        }
+        return Ok(all_pairs);
+    }
+
+    /// run dijkstra from every node in turn, which needs the weights to be non-negative
+    pub fn repeated_dijkstra(graph: &Graph<W>) -> AllPairs<W> {
+        let mut all_pairs = AllPairs::unreachable(graph.number_of_nodes);
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        for from_idx in 0..graph.number_of_nodes {
+            let settled = context.dijkstra_from(graph, from_idx);
+            for &node_idx in &settled {
+                let node = context.nodes_visited[node_idx].expect("Expected a settled node");
+                all_pairs.distances[from_idx][node_idx] = Some(node.dist_to_node);
+            }
+            // the parents have already been settled, so the hop to each parent is already known
+            for &node_idx in settled.iter().skip(1) {
+                let parent_idx = context.nodes_visited[node_idx]
+                    .expect("Expected a settled node")
+                    .parent_idx;
+                all_pairs.next_hops[from_idx][node_idx] = if parent_idx == from_idx {
+                    Some(node_idx)
+                } else {
+                    all_pairs.next_hops[from_idx][parent_idx]
+                };
+            }
+        }
+        return all_pairs;
+    }
+
+    /// Johnson's algorithm: find a potential for every node with Bellman-Ford, as if from an extra
+    /// node with an edge of 0 to every other node. Reweighting each edge by the difference in
+    /// potential makes every weight non-negative without changing which routes are shortest, so
+    /// dijkstra can be run from every node. A negative cycle is an error.
+    pub fn johnson(graph: &Graph<W>) -> Result<AllPairs<W>, DijkstraError> {
+        if !has_negative_weights(graph) {
+            return Ok(AllPairs::repeated_dijkstra(graph));
+        }
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let all_nodes: Vec<usize> = (0..graph.number_of_nodes).collect();
+        context.bellman_ford_from_sources(graph, &all_nodes)?;
+        // at most 0, as every node starts at 0
+        let potentials: Vec<W> = all_nodes
+            .iter()
+            .map(|&node_idx| {
+                context
+                    .dist_to(node_idx)
+                    .expect("Expected every node to be a source")
+            })
+            .collect();
+
+        // the potentials are shortest distances, so potential[from] + weight >= potential[to]
+        let mut reweighted_edges = Vec::with_capacity(graph.edges.len());
+        for edge in graph.connections.iter().flatten() {
+            let reweighted = edge
+                .weight
+                .checked_add(potentials[edge.index_first])
+                .and_then(|weight| weight.checked_sub(potentials[edge.index_second]));
+            // an edge too long to reweight is too long to be on any route that can be represented
+            if let Some(weight) = reweighted {
+                reweighted_edges.push(Edge::new_directed(
+                    edge.index_first,
+                    edge.index_second,
+                    weight,
+                ));
+            }
+        }
+        let reweighted_graph = Graph::new(graph.graph_nodes.clone(), reweighted_edges)
+            .expect("Expected the reweighted edges to join the same nodes");
+
+        let mut all_pairs = AllPairs::repeated_dijkstra(&reweighted_graph);
+        for (from_idx, row) in all_pairs.distances.iter_mut().enumerate() {
+            for (to_idx, dist) in row.iter_mut().enumerate() {
+                *dist = dist
+                    .and_then(|dist| dist.checked_add(potentials[to_idx]))
+                    .and_then(|dist| dist.checked_sub(potentials[from_idx]));
+                if dist.is_none() {
+                    all_pairs.next_hops[from_idx][to_idx] = None;
+                }
+            }
+        }
+        return Ok(all_pairs);
+    }
+
+    /// every node reaches itself with a distance of 0, and nothing else
+    fn unreachable(number_of_nodes: usize) -> AllPairs<W> {
+        let mut all_pairs = AllPairs {
+            distances: vec![vec![None; number_of_nodes]; number_of_nodes],
+            next_hops: vec![vec![None; number_of_nodes]; number_of_nodes],
+        };
+        for node_idx in 0..number_of_nodes {
+            all_pairs.distances[node_idx][node_idx] = Some(W::zero());
+            all_pairs.next_hops[node_idx][node_idx] = Some(node_idx);
+        }
+        return all_pairs;
+    }
+
+    /// the nodes of the shortest route from start to end, following the next hops, or None if
+    /// the end can't be reached
+    pub fn route(&self, start_idx: usize, end_idx: usize) -> Option<Vec<usize>> {
+        let mut nodes_in_order = vec![start_idx];
+        let mut idx = start_idx;
+        while idx != end_idx {
+            idx = self.next_hops[idx][end_idx]?;
+            nodes_in_order.push(idx);
+        }
+        return Some(nodes_in_order);
+    }
+
+    /// the distance matrix as CSV, with the node names as the column and row headers and an
+    /// empty field where there is no route, i.e.
+    ///
+    /// ```text
+    /// ,A,B
+    /// A,0,5
+    /// B,,0
+    /// ```
+    pub fn distances_to_csv(&self, graph: &Graph<W>) -> String {
+        let node_names: Vec<String> = graph
+            .graph_nodes
+            .iter()
+            .map(|node| csv_field(&node.node_name))
+            .collect();
+        let mut csv = format!(",{}\n", node_names.join(","));
+        for (from_idx, row) in self.distances.iter().enumerate() {
+            let fields: Vec<String> = row
+                .iter()
+                .map(|dist| match dist {
+                    Some(dist) => return dist.to_string(),
+                    None => return String::new(),
+                })
+                .collect();
+            csv.push_str(&format!("{},{}\n", node_names[from_idx], fields.join(",")));
+        }
+        return csv;
+    }
+}
+
+pub(crate) fn has_negative_weights<W: Weight>(graph: &Graph<W>) -> bool {
+    return graph
+        .connections
+        .iter()
+        .flatten()
+        .any(|edge| edge.weight < W::zero());
+}
+
+/// with more edges than V^2 / log2(V), dijkstra from every node costs more than Floyd-Warshall
+fn is_dense<W>(graph: &Graph<W>) -> bool {
+    let number_of_nodes = graph.number_of_nodes;
+    let number_of_edges: usize = graph.connections.iter().map(|edges| edges.len()).sum();
+    let log_nodes = (usize::BITS - number_of_nodes.leading_zeros()) as usize;
+    return number_of_edges.saturating_mul(log_nodes) >= number_of_nodes * number_of_nodes;
+}
+
+/// quote a field that contains a comma, quote or line break
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", text.replace('"', "\"\""));
+    }
+    return text.to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_builder::GraphBuilder;
+    use crate::parse_input::{Edge, GraphNode};
+    use crate::random_graph::RandomGraphs;
+
+    use std::fs;
+
+    #[test]
+    fn floyd_warshall_matches_repeated_dijkstra() {
+        let contents = fs::read_to_string("src/test/uk.txt").unwrap();
+        let graph = Graph::new_from_string(&contents).unwrap();
+        let floyd_warshall = AllPairs::floyd_warshall(&graph).unwrap();
+        let repeated_dijkstra = AllPairs::repeated_dijkstra(&graph);
+        assert_eq!(floyd_warshall.distances, repeated_dijkstra.distances);
+
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        for start_idx in 0..graph.number_of_nodes {
+            for end_idx in 0..graph.number_of_nodes {
+                let expected = context.dijkstra(&graph, start_idx, end_idx).ok();
+                for all_pairs in [&floyd_warshall, &repeated_dijkstra] {
+                    assert_eq!(
+                        all_pairs.distances[start_idx][end_idx],
+                        expected.as_ref().map(|(dist, _)| *dist)
+                    );
+                    // ties can be broken either way, so check the route has the right distance
+                    let route = all_pairs.route(start_idx, end_idx);
+                    assert_eq!(route.is_some(), expected.is_some());
+                    if let Some(route) = route {
+                        let total: usize = route
+                            .windows(2)
+                            .map(|leg| {
+                                graph.connections[leg[0]]
+                                    .iter()
+                                    .find(|edge| edge.index_second == leg[1])
+                                    .unwrap()
+                                    .weight
+                            })
+                            .sum();
+                        assert_eq!(Some(total), all_pairs.distances[start_idx][end_idx]);
+                    }
+                }
+            }
+        }
+    }
+    #[test]
+    fn directed_and_negative_weights() {
+        let graph = Graph::<i64>::new_from_string_with_signed_weights(
+            "3\nA\nB\nC\n\n3 directed\nA B 4\nB C -3\nA C 2\n\nA C",
+        )
+        .unwrap();
+        let all_pairs = AllPairs::new(&graph).unwrap();
+        assert_eq!(
+            all_pairs.distances,
+            vec![
+                vec![Some(0), Some(4), Some(1)],
+                vec![None, Some(0), Some(-3)],
+                vec![None, None, Some(0)],
+            ]
+        );
+        assert_eq!(all_pairs.route(0, 2), Some(vec![0, 1, 2]));
+        assert_eq!(all_pairs.route(2, 0), None);
+        assert_eq!(all_pairs.route(1, 1), Some(vec![1]));
+
+        let graph = Graph::<i64>::new_from_string_with_signed_weights(
+            "3\nA\nB\nC\n\n3 directed\nA B 1\nB C -2\nC B 1\n\nA C",
+        )
+        .unwrap();
+        assert!(matches!(
+            AllPairs::new(&graph),
+            Err(DijkstraError::NegativeCycle { .. })
+        ));
+    }
+    #[test]
+    fn johnson_matches_floyd_warshall() {
+        let number_of_nodes = 30;
+        // edges to a later node can be negative, but any cycle has to come back along an edge
+        // long enough to make up for them
+        let graph =
+            RandomGraphs::new(11).graph(number_of_nodes, 60, |random, first_idx, second_idx| {
+                if first_idx < second_idx {
+                    let weight = random.next(20) as i64 - 10;
+                    return Some(Edge::new_directed(first_idx, second_idx, weight));
+                } else if first_idx > second_idx {
+                    let weight = (10 * number_of_nodes + random.next(20)) as i64;
+                    return Some(Edge::new_directed(first_idx, second_idx, weight));
+                }
+                return None;
+            });
+        assert!(has_negative_weights(&graph));
+        assert!(!is_dense(&graph));
+
+        let johnson = AllPairs::johnson(&graph).unwrap();
+        let floyd_warshall = AllPairs::floyd_warshall(&graph).unwrap();
+        assert_eq!(johnson.distances, floyd_warshall.distances);
+        assert_eq!(AllPairs::new(&graph), Ok(johnson.clone()));
+        for start_idx in 0..number_of_nodes {
+            for end_idx in 0..number_of_nodes {
+                let route = johnson.route(start_idx, end_idx);
+                assert_eq!(
+                    route.is_some(),
+                    johnson.distances[start_idx][end_idx].is_some()
+                );
+                if let Some(route) = route {
+                    let total: i64 = route
+                        .windows(2)
+                        .map(|leg| {
+                            graph.connections[leg[0]]
+                                .iter()
+                                .find(|edge| edge.index_second == leg[1])
+                                .unwrap()
+                                .weight
+                        })
+                        .sum();
+                    assert_eq!(Some(total), johnson.distances[start_idx][end_idx]);
+                }
+            }
+        }
+    }
+    #[test]
+    fn johnson_with_negative_cycle() {
+        let graph = Graph::<i64>::new_from_string_with_signed_weights(
+            "4\nA\nB\nC\nD\n\n4 directed\nA B 1\nB C -2\nC B 1\nC D 3\n\nA D",
+        )
+        .unwrap();
+        assert!(matches!(
+            AllPairs::johnson(&graph),
+            Err(DijkstraError::NegativeCycle { .. })
+        ));
+    }
+    #[test]
+    fn choose_by_density() {
+        let graph_nodes: Vec<GraphNode> = (0..16)
+            .map(|idx| GraphNode::new(idx, format!("n{}", idx)))
+            .collect();
+        let path = (0..15).map(|idx| Edge::new(idx, idx + 1, 1)).collect();
+        assert!(!is_dense(&Graph::new(graph_nodes.clone(), path).unwrap()));
+        let mut complete = Vec::new();
+        for first_idx in 0..16 {
+            for second_idx in first_idx + 1..16 {
+                complete.push(Edge::new(first_idx, second_idx, 1));
+            }
+        }
+        assert!(is_dense(&Graph::new(graph_nodes, complete).unwrap()));
+    }
+    #[test]
+    fn distances_as_csv() {
+        // names in the text format can't have spaces, but names given to the builder can
+        let graph = GraphBuilder::new()
+            .node("A")
+            .node("B, north")
+            .node("C")
+            .edge("A", "B, north", 5)
+            .build()
+            .unwrap();
+        let all_pairs = AllPairs::new(&graph).unwrap();
+        assert_eq!(
+            all_pairs.distances_to_csv(&graph),
+            ",A,\"B, north\",C\nA,0,5,\n\"B, north\",5,0,\nC,,,0\n"
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::parse_input::Edge;
+use crate::search_context::{Node, SearchContext};
+use crate::weight::Weight;
+
+impl<W: Weight> SearchContext<W> {
+    /// find the shortest route from start to end when some of the edge weights are negative.
+    /// A negative weight on a bidirectional edge is a negative cycle on its own, as the edge can
+    /// be travelled back and forth.
+    pub fn bellman_ford(
+        &mut self,
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        return self.bellman_ford_along(graph, start_idx, end_idx, &|_| true);
+    }
+
+    /// as bellman_ford, but only travelling along the usable edges, i.e. for the spur routes of
+    /// `k_shortest_paths`
+    pub(crate) fn bellman_ford_along(
+        &mut self,
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+        is_usable: &dyn Fn(&Edge<W>) -> bool,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        let has_overflowed =
+            self.bellman_ford_from_sources_along(graph, &[start_idx], is_usable)?;
+        match self.dist_to(end_idx) {
+            Some(dist) => return Ok((dist, self.route_to(start_idx, end_idx))),
+            None if has_overflowed => {
+                return Err(DijkstraError::distance_overflow(graph, start_idx, end_idx))
+            }
+            None => return Err(DijkstraError::disconnected(graph, start_idx, end_idx)),
+        }
+    }
+
+    /// find the shortest distance to every node that can be reached from the start, relaxing
+    /// every edge until nothing changes. If an edge can still be relaxed after a round for every
+    /// node, there is a negative cycle. Returns whether any distance overflowed.
+    pub(crate) fn bellman_ford_from(
+        &mut self,
+        graph: &Graph<W>,
+        start_idx: usize,
+    ) -> Result<bool, DijkstraError> {
+        return self.bellman_ford_from_sources(graph, &[start_idx]);
+    }
+
+    /// as bellman_ford_from, but starting from every one of the sources at a distance of 0
+    pub(crate) fn bellman_ford_from_sources(
+        &mut self,
+        graph: &Graph<W>,
+        sources: &[usize],
+    ) -> Result<bool, DijkstraError> {
+        return self.bellman_ford_from_sources_along(graph, sources, &|_| true);
+    }
+
+    fn bellman_ford_from_sources_along(
+        &mut self,
+        graph: &Graph<W>,
+        sources: &[usize],
+        is_usable: &dyn Fn(&Edge<W>) -> bool,
+    ) -> Result<bool, DijkstraError> {
+        self.begin_query(graph.number_of_nodes);
+        for &source_idx in sources {
+            self.visit(Node::new(source_idx, source_idx, W::zero()));
+        }
+
+        let mut has_overflowed = false;
+        for round in 0..graph.number_of_nodes {
+            let mut has_changed = false;
+            for current_idx in 0..graph.number_of_nodes {
+                let dist_to_current = match self.dist_to(current_idx) {
+                    Some(dist) => dist,
+                    None => continue,
+                };
+                for edge in graph.connections[current_idx]
+                    .iter()
+                    .filter(|edge| is_usable(edge))
+                {
+                    let next_idx = edge.index_second;
+                    let dist_through_current = match dist_to_current.checked_add(edge.weight) {
+                        Some(dist) => dist,
+                        None => {
+                            has_overflowed = true;
+                            continue;
+                        }
+                    };
+                    if let Some(dist_to_next) = self.dist_to(next_idx) {
+                        if dist_to_next <= dist_through_current {
+                            continue;
+                        }
+                    }
+                    self.visit(Node::new(next_idx, current_idx, dist_through_current));
+                    // the shortest paths use at most number_of_nodes - 1 edges, so still finding
+                    // a shorter path in the last round means going round a negative cycle
+                    if round == graph.number_of_nodes - 1 {
+                        return Err(self.negative_cycle_through(graph, next_idx));
+                    }
+                    has_changed = true;
+                }
+            }
+            if !has_changed {
+                break;
+            }
+        }
+        return Ok(has_overflowed);
+    }
+
+    /// the node was improved in the last round, so following its parents for a round's worth of
+    /// nodes is certain to end up inside the negative cycle
+    fn negative_cycle_through(&self, graph: &Graph<W>, node_idx: usize) -> DijkstraError {
+        let parent_of = |idx: usize| {
+            return self.nodes_visited[idx]
+                .expect("Expected every parent to have been reached")
+                .parent_idx;
+        };
+        let mut idx_in_cycle = node_idx;
+        for _ in 0..graph.number_of_nodes {
+            idx_in_cycle = parent_of(idx_in_cycle);
+        }
+
+        let mut cycle = vec![idx_in_cycle];
+        let mut idx = parent_of(idx_in_cycle);
+        while idx != idx_in_cycle {
+            cycle.push(idx);
+            idx = parent_of(idx);
+        }
+        // parents lead backwards around the cycle
+        cycle.reverse();
+        return DijkstraError::NegativeCycle {
+            cycle: cycle
+                .into_iter()
+                .map(|idx| graph.graph_nodes[idx].node_name.to_string())
+                .collect(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    #[test]
+    fn negative_weights() {
+        let graph = Graph::<i64>::new_from_string_with_signed_weights(
+            "4\nA\nB\nC\nD\n\n4 directed\nA B 4\nA C 2\nB D -3\nC D 1\n\nA D",
+        )
+        .unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        assert_eq!(context.bellman_ford(&graph, 0, 3), Ok((1, vec![0, 1, 3])));
+        assert_eq!(
+            context.bellman_ford(&graph, 3, 0),
+            Err(DijkstraError::Disconnected {
+                start_idx: 3,
+                end_idx: 0,
+                start_name: "D".to_string(),
+                end_name: "A".to_string()
+            })
+        );
+    }
+    #[test]
+    fn negative_cycle_is_returned_by_name() {
+        let graph = Graph::<i64>::new_from_string_with_signed_weights(
+            "5\nA\nB\nC\nD\nE\n\n5 directed\nA B 1\nB C 2\nC D -4\nD B 1\nE A 1\n\nA D",
+        )
+        .unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let cycle = match context.bellman_ford(&graph, 0, 3) {
+            Err(DijkstraError::NegativeCycle { cycle }) => cycle,
+            other => panic!("Expected a negative cycle, found {:?}", other),
+        };
+        // the cycle can start from any of its nodes
+        let start = cycle.iter().position(|name| name == "B").unwrap();
+        let rotated: Vec<&str> = cycle[start..]
+            .iter()
+            .chain(&cycle[..start])
+            .map(|name| name.as_str())
+            .collect();
+        assert_eq!(rotated, vec!["B", "C", "D"]);
+
+        // and from E, which reaches the cycle through A
+        assert!(matches!(
+            context.bellman_ford(&graph, 4, 4),
+            Err(DijkstraError::NegativeCycle { .. })
+        ));
+        // a negative bidirectional edge can be travelled back and forth
+        let graph = Graph::<i64>::new_from_string_with_signed_weights(
+            "3\nA\nB\nC\n\n2\nA B 1\nB C -1\n\nA C",
+        )
+        .unwrap();
+        assert!(matches!(
+            context.bellman_ford(&graph, 0, 2),
+            Err(DijkstraError::NegativeCycle { .. })
+        ));
+    }
+    #[test]
+    fn matches_dijkstra_without_negative_weights() {
+        let contents = fs::read_to_string("src/test/uk.txt").unwrap();
+        let graph = Graph::new_from_string(&contents).unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let mut bellman_ford_context = SearchContext::new(graph.number_of_nodes);
+        for start_idx in 0..graph.number_of_nodes {
+            for end_idx in 0..graph.number_of_nodes {
+                assert_eq!(
+                    bellman_ford_context
+                        .bellman_ford(&graph, start_idx, end_idx)
+                        .map(|(dist, _)| dist),
+                    context
+                        .dijkstra(&graph, start_idx, end_idx)
+                        .map(|(dist, _)| dist)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::find_path::Algorithm;
+use crate::search_context::{Node, SearchContext};
+use crate::weight::Weight;
+
+/// The edge weights a graph has, to tell whether a breadth first search can be used instead of
+/// dijkstra
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightProfile<W = usize> {
+    /// every edge has this weight, i.e. the graph is unweighted, so see `SearchContext::bfs`
+    Uniform(W),
+    /// every edge is either 0 or this weight, i.e. free transfers and paid hops, so see
+    /// `SearchContext::zero_one_bfs`
+    ZeroOrUniform(W),
+    /// any other weights, including negative weights
+    Varied,
+}
+
+impl<W: Weight> Graph<W> {
+    /// look through the edge weights, for `Algorithm::for_weight_profile`. A graph without edges
+    /// is uniform.
+    pub fn weight_profile(&self) -> WeightProfile<W> {
+        let mut nonzero_weight = None;
+        let mut has_zero_weight = false;
+        for edge in self.connections.iter().flatten() {
+            if edge.weight < W::zero() {
+                return WeightProfile::Varied;
+            }
+            if edge.weight == W::zero() {
+                has_zero_weight = true;
+                continue;
+            }
+            match nonzero_weight {
+                None => nonzero_weight = Some(edge.weight),
+                Some(weight) if weight != edge.weight => return WeightProfile::Varied,
+                Some(_) => {}
+            }
+        }
+        match (nonzero_weight, has_zero_weight) {
+            (Some(weight), true) => return WeightProfile::ZeroOrUniform(weight),
+            (Some(weight), false) => return WeightProfile::Uniform(weight),
+            (None, _) => return WeightProfile::Uniform(W::zero()),
+        }
+    }
+}
+
+impl<'a, W: Weight> Algorithm<'a, W> {
+    /// the quickest search that finds routes as short as dijkstra's for graphs with these weights.
+    /// When several routes are equally short, it may choose a different one.
+    pub fn for_weight_profile(profile: WeightProfile<W>) -> Algorithm<'a, W> {
+        match profile {
+            WeightProfile::Uniform(_) => return Algorithm::Bfs,
+            WeightProfile::ZeroOrUniform(_) => return Algorithm::ZeroOneBfs,
+            WeightProfile::Varied => return Algorithm::Dijkstra,
+        }
+    }
+}
+
+impl<W: Weight> SearchContext<W> {
+    /// breadth first search, for graphs whose edges all have the same weight
+    /// (`WeightProfile::Uniform`). Nodes are reached in order of the number of edges from the
+    /// start, so the first path found to a node is a shortest one and no priority queue is
+    /// needed. Other weights give a route, but it may not be the shortest.
+    pub fn bfs(
+        &mut self,
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        self.begin_query(graph.number_of_nodes);
+        self.visit(Node::new(start_idx, start_idx, W::zero()));
+        self.queue.push_back((W::zero(), start_idx));
+
+        let mut has_overflowed = false;
+        while let Some((dist_to_current, current_idx)) = self.queue.pop_front() {
+            if current_idx == end_idx {
+                return Ok((dist_to_current, self.route_to(start_idx, end_idx)));
+            }
+            for edge in &graph.connections[current_idx] {
+                let next_idx = edge.index_second;
+                if self.dist_to(next_idx).is_some() {
+                    continue;
+                }
+                let dist_through_current = match dist_to_current.checked_add(edge.weight) {
+                    Some(dist) => dist,
+                    None => {
+                        has_overflowed = true;
+                        continue;
+                    }
+                };
+                self.visit(Node::new(next_idx, current_idx, dist_through_current));
+                self.queue.push_back((dist_through_current, next_idx));
+            }
+        }
+
+        if has_overflowed {
+            return Err(DijkstraError::distance_overflow(graph, start_idx, end_idx));
+        }
+        return Err(DijkstraError::disconnected(graph, start_idx, end_idx));
+    }
+
+    /// 0-1 breadth first search, for graphs whose edges are all either 0 or the same weight
+    /// (`WeightProfile::ZeroOrUniform`). A node reached along a 0 edge goes to the front of the
+    /// queue and any other node to the back, which keeps the queue in order of distance without
+    /// a priority queue. Other weights give a route, but it may not be the shortest.
+    pub fn zero_one_bfs(
+        &mut self,
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        self.begin_query(graph.number_of_nodes);
+        self.visit(Node::new(start_idx, start_idx, W::zero()));
+        self.queue.push_back((W::zero(), start_idx));
+
+        let mut has_overflowed = false;
+        while let Some((dist_to_current, current_idx)) = self.queue.pop_front() {
+            // a shorter path to this node was found after this entry was added
+            if Some(dist_to_current) > self.dist_to(current_idx) {
+                continue;
+            }
+            if current_idx == end_idx {
+                return Ok((dist_to_current, self.route_to(start_idx, end_idx)));
+            }
+            for edge in &graph.connections[current_idx] {
+                let next_idx = edge.index_second;
+                let dist_through_current = match dist_to_current.checked_add(edge.weight) {
+                    Some(dist) => dist,
+                    None => {
+                        has_overflowed = true;
+                        continue;
+                    }
+                };
+                if let Some(dist_to_next) = self.dist_to(next_idx) {
+                    if dist_to_next <= dist_through_current {
+                        continue;
+                    }
+                }
+                self.visit(Node::new(next_idx, current_idx, dist_through_current));
+                if edge.weight == W::zero() {
+                    self.queue.push_front((dist_through_current, next_idx));
+                } else {
+                    self.queue.push_back((dist_through_current, next_idx));
+                }
+            }
+        }
+
+        if has_overflowed {
+            return Err(DijkstraError::distance_overflow(graph, start_idx, end_idx));
+        }
+        return Err(DijkstraError::disconnected(graph, start_idx, end_idx));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_path::PathFinder;
+    use crate::parse_input::Edge;
+    use crate::random_graph::RandomGraphs;
+    use crate::route::Route;
+
+    use std::fs;
+
+    /// a random graph whose edge weights are picked from the given weights
+    fn random_graph(random: &mut RandomGraphs, weights: &[usize]) -> Graph {
+        let (number_of_nodes, number_of_edges) = (1 + random.next(40), random.next(100));
+        return random.graph(
+            number_of_nodes,
+            number_of_edges,
+            |random, from_idx, to_idx| {
+                let weight = weights[random.next(weights.len())];
+                if random.next(2) == 0 {
+                    return Some(Edge::new_directed(from_idx, to_idx, weight));
+                }
+                return Some(Edge::new(from_idx, to_idx, weight));
+            },
+        );
+    }
+
+    #[test]
+    fn detects_weight_profile() {
+        let profile_of = |edges: &str| {
+            let contents = format!("3\nA\nB\nC\n\n{}\n\nA C", edges);
+            return Graph::<i64>::new_from_string_with_signed_weights(&contents)
+                .unwrap()
+                .weight_profile();
+        };
+        assert_eq!(profile_of("2\nA B 1\nB C 1"), WeightProfile::Uniform(1));
+        assert_eq!(profile_of("2\nA B 3\nB C 3"), WeightProfile::Uniform(3));
+        assert_eq!(
+            profile_of("2\nA B 0\nB C 2"),
+            WeightProfile::ZeroOrUniform(2)
+        );
+        assert_eq!(profile_of("2\nA B 0\nB C 0"), WeightProfile::Uniform(0));
+        assert_eq!(profile_of("0"), WeightProfile::Uniform(0));
+        assert_eq!(profile_of("2\nA B 1\nB C 2"), WeightProfile::Varied);
+        assert_eq!(profile_of("2\nA -> B -1\nB -> C -1"), WeightProfile::Varied);
+        assert!(matches!(
+            Algorithm::for_weight_profile(WeightProfile::ZeroOrUniform(2)),
+            Algorithm::ZeroOneBfs
+        ));
+    }
+    #[test]
+    fn bfs_matches_dijkstra() {
+        let mut random = RandomGraphs::new(3);
+        for weights in [vec![1], vec![5], vec![0, 1], vec![0, 0, 4]] {
+            for _ in 0..20 {
+                let graph = random_graph(&mut random, &weights);
+                let mut context = SearchContext::new(graph.number_of_nodes);
+                let mut dijkstra_context = SearchContext::new(graph.number_of_nodes);
+                for start_idx in 0..graph.number_of_nodes {
+                    for end_idx in 0..graph.number_of_nodes {
+                        let expected = dijkstra_context
+                            .dijkstra(&graph, start_idx, end_idx)
+                            .map(|(dist, _)| dist);
+                        let found = if weights.len() == 1 {
+                            context.bfs(&graph, start_idx, end_idx)
+                        } else {
+                            context.zero_one_bfs(&graph, start_idx, end_idx)
+                        };
+                        // ties may be broken differently, but the route must be as short
+                        let found = found.and_then(|(dist, nodes_in_order)| {
+                            let route = Route::new(&graph, nodes_in_order)?;
+                            assert_eq!(route.total_distance, dist);
+                            return Ok(dist);
+                        });
+                        assert_eq!(found, expected);
+                    }
+                }
+            }
+        }
+    }
+    #[test]
+    fn path_finder_chooses_bfs() {
+        let contents = fs::read_to_string("src/test/uk.txt").unwrap();
+        let graph = Graph::new_from_string(&contents).unwrap();
+        assert_eq!(graph.weight_profile(), WeightProfile::Varied);
+
+        let unweighted = "4\nA\nB\nC\nD\n\n4\nA B 1\nB C 1\nC D 1\nA C 1\n\nA D\nD A";
+        let mut path_finder = PathFinder::new_from_string(unweighted).unwrap();
+        let algorithm = Algorithm::for_weight_profile(path_finder.graph.weight_profile());
+        path_finder.find_multiple_routes(algorithm);
+        let found: Vec<String> = path_finder
+            .solutions
+            .iter()
+            .map(|solution| solution.as_ref().unwrap().to_string())
+            .collect();
+        assert_eq!(found, vec!["A->C->D, dist 2", "D->C->A, dist 2"]);
+    }
+    #[test]
+    fn overflowing_distances_are_an_error() {
+        let graph = Graph::<u8>::new_from_string_with_weights(
+            "4\nA\nB\nC\nD\n\n3\nA B 200\nB C 200\nC D 0\n\nA D",
+        )
+        .unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        assert_eq!(
+            context.zero_one_bfs(&graph, 0, 3),
+            Err(DijkstraError::DistanceOverflow {
+                start_idx: 0,
+                end_idx: 3,
+                start_name: "A".to_string(),
+                end_name: "D".to_string()
+            })
+        );
+        let graph =
+            Graph::<u8>::new_from_string_with_weights("3\nA\nB\nC\n\n2\nA B 200\nB C 200\n\nA C")
+                .unwrap();
+        assert_eq!(
+            context.bfs(&graph, 0, 2),
+            Err(DijkstraError::DistanceOverflow {
+                start_idx: 0,
+                end_idx: 2,
+                start_name: "A".to_string(),
+                end_name: "C".to_string()
+            })
+        );
+        assert_eq!(context.bfs(&graph, 0, 1), Ok((200, vec![0, 1])));
+    }
+}
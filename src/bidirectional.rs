@@ -0,0 +1,290 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::parse_input::Edge;
+use crate::search_context::{FrontierEntry, Node, SearchContext};
+use crate::weight::Weight;
+
+/// The best path found so far from the start to the end, through the node where the two
+/// searches met.
+#[derive(Debug, Clone, Copy)]
+struct Meeting<W> {
+    dist: W,
+    node_idx: usize,
+}
+
+impl<W: Weight> SearchContext<W> {
+    /// find the shortest route from start to end with two dijkstra searches, one forwards from the
+    /// start and one backwards from the end along `reverse_connections`, always growing the one
+    /// with the closer frontier. The search stops once the two frontiers together are at least as
+    /// long as the best path found through a node reached by both searches.
+    pub fn bidirectional_dijkstra(
+        &mut self,
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        let mut reverse = self
+            .reverse
+            .take()
+            .unwrap_or_else(|| Box::new(SearchContext::new(graph.number_of_nodes)));
+        let result = self.meet_in_the_middle(&mut reverse, graph, start_idx, end_idx);
+        self.reverse = Some(reverse);
+        return result;
+    }
+
+    fn meet_in_the_middle(
+        &mut self,
+        reverse: &mut SearchContext<W>,
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        self.begin_query(graph.number_of_nodes);
+        reverse.begin_query(graph.number_of_nodes);
+        self.begin_at(start_idx);
+        reverse.begin_at(end_idx);
+
+        let mut best: Option<Meeting<W>> = None;
+        if start_idx == end_idx {
+            best = Some(Meeting {
+                dist: W::zero(),
+                node_idx: start_idx,
+            });
+        }
+        let mut has_overflowed = false;
+        while let (Some(forward_entry), Some(reverse_entry)) =
+            (self.peek_frontier(), reverse.peek_frontier())
+        {
+            // every path still to be found is at least as long as the two frontiers together
+            if let Some(meeting) = best {
+                let shortest_remaining = forward_entry
+                    .dist_to_node
+                    .checked_add(reverse_entry.dist_to_node);
+                if shortest_remaining.is_none_or(|dist| dist >= meeting.dist) {
+                    break;
+                }
+            }
+
+            if forward_entry.dist_to_node <= reverse_entry.dist_to_node {
+                self.frontier.pop();
+                let edges = &graph.connections[forward_entry.node_idx];
+                has_overflowed |= self.expand(
+                    reverse,
+                    forward_entry,
+                    edges,
+                    |edge| edge.index_second,
+                    &mut best,
+                );
+            } else {
+                reverse.frontier.pop();
+                let edges = &graph.reverse_connections[reverse_entry.node_idx];
+                has_overflowed |= reverse.expand(
+                    self,
+                    reverse_entry,
+                    edges,
+                    |edge| edge.index_first,
+                    &mut best,
+                );
+            }
+        }
+
+        match best {
+            Some(meeting) => {
+                let mut nodes_in_order = self.route_to(start_idx, meeting.node_idx);
+                // the reverse search's parents lead from the meeting node towards the end
+                let mut to_end = reverse.route_to(end_idx, meeting.node_idx);
+                to_end.reverse();
+                nodes_in_order.extend_from_slice(&to_end[1..]);
+                return Ok((meeting.dist, nodes_in_order));
+            }
+            None if has_overflowed => {
+                return Err(DijkstraError::distance_overflow(graph, start_idx, end_idx))
+            }
+            None => return Err(DijkstraError::disconnected(graph, start_idx, end_idx)),
+        }
+    }
+
+    fn begin_at(&mut self, node_idx: usize) {
+        self.visit(Node::new(node_idx, node_idx, W::zero()));
+        self.frontier.push(FrontierEntry {
+            priority: W::zero(),
+            dist_to_node: W::zero(),
+            node_idx,
+        });
+    }
+
+    /// relax the edges of a settled node in this direction, recording a better meeting with the
+    /// other search whenever a node it has reached is improved. `next_node` gives the node at the
+    /// far end of an edge in this direction. Returns whether any distance overflowed.
+    fn expand(
+        &mut self,
+        other: &SearchContext<W>,
+        entry: FrontierEntry<W>,
+        edges: &[Edge<W>],
+        next_node: impl Fn(&Edge<W>) -> usize,
+        best: &mut Option<Meeting<W>>,
+    ) -> bool {
+        let mut has_overflowed = false;
+        for edge in edges {
+            let next_idx = next_node(edge);
+            let dist_through_current = match entry.dist_to_node.checked_add(edge.weight) {
+                Some(dist) => dist,
+                None => {
+                    has_overflowed = true;
+                    continue;
+                }
+            };
+            if let Some(dist_to_next) = self.dist_to(next_idx) {
+                if dist_to_next <= dist_through_current {
+                    continue;
+                }
+            }
+            self.visit(Node::new(next_idx, entry.node_idx, dist_through_current));
+            self.frontier.push(FrontierEntry {
+                priority: dist_through_current,
+                dist_to_node: dist_through_current,
+                node_idx: next_idx,
+            });
+
+            if let Some(dist_from_other) = other.dist_to(next_idx) {
+                match dist_through_current.checked_add(dist_from_other) {
+                    Some(dist) if best.is_none_or(|meeting| dist < meeting.dist) => {
+                        *best = Some(Meeting {
+                            dist,
+                            node_idx: next_idx,
+                        });
+                    }
+                    Some(_) => {}
+                    None => has_overflowed = true,
+                }
+            }
+        }
+        return has_overflowed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_input::GraphNode;
+    use crate::random_graph::RandomGraphs;
+
+    use std::fs;
+
+    #[test]
+    fn matches_dijkstra_on_uk_routes() {
+        let contents = fs::read_to_string("src/test/uk.txt").unwrap();
+        let graph = Graph::new_from_string(&contents).unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let mut bidirectional_context = SearchContext::new(graph.number_of_nodes);
+        for start_idx in 0..graph.number_of_nodes {
+            for end_idx in 0..graph.number_of_nodes {
+                let expected = context.dijkstra(&graph, start_idx, end_idx);
+                let found =
+                    bidirectional_context.bidirectional_dijkstra(&graph, start_idx, end_idx);
+                assert_eq!(
+                    found.as_ref().map(|(dist, _)| *dist),
+                    expected.as_ref().map(|(dist, _)| *dist)
+                );
+                if let Ok((dist, nodes_in_order)) = found {
+                    assert_eq!(nodes_in_order[0], start_idx);
+                    assert_eq!(nodes_in_order[nodes_in_order.len() - 1], end_idx);
+                    let total: usize = nodes_in_order
+                        .windows(2)
+                        .map(|leg| {
+                            graph.connections[leg[0]]
+                                .iter()
+                                .find(|edge| edge.index_second == leg[1])
+                                .unwrap()
+                                .weight
+                        })
+                        .sum();
+                    assert_eq!(total, dist);
+                }
+            }
+        }
+    }
+    #[test]
+    fn matches_dijkstra_on_a_random_directed_graph() {
+        let number_of_nodes = 40;
+        let graph = RandomGraphs::new(7).graph(number_of_nodes, 100, |random, from_idx, to_idx| {
+            if from_idx == to_idx {
+                return None;
+            }
+            return Some(Edge::new_directed(from_idx, to_idx, random.next(20)));
+        });
+
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let mut bidirectional_context = SearchContext::new(graph.number_of_nodes);
+        for start_idx in 0..number_of_nodes {
+            for end_idx in 0..number_of_nodes {
+                assert_eq!(
+                    bidirectional_context
+                        .bidirectional_dijkstra(&graph, start_idx, end_idx)
+                        .map(|(dist, _)| dist),
+                    context
+                        .dijkstra(&graph, start_idx, end_idx)
+                        .map(|(dist, _)| dist)
+                );
+            }
+        }
+    }
+    #[test]
+    fn follows_one_way_edges_backwards() {
+        let graph = Graph::new_from_string(
+            "4\nA\nB\nC\nD\n\n4\nA -> B 1\nB -> D 1\nD -> C 1\nC <-> A 10\n\nA D\nD A",
+        )
+        .unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        assert_eq!(
+            context.bidirectional_dijkstra(&graph, 0, 3),
+            Ok((2, vec![0, 1, 3]))
+        );
+        assert_eq!(
+            context.bidirectional_dijkstra(&graph, 3, 0),
+            Ok((11, vec![3, 2, 0]))
+        );
+        assert_eq!(
+            context.bidirectional_dijkstra(&graph, 2, 2),
+            Ok((0, vec![2]))
+        );
+    }
+    #[test]
+    fn disconnected_and_overflowing_routes() {
+        let graph_nodes = vec![
+            GraphNode::new(0, "A".to_string()),
+            GraphNode::new(1, "B".to_string()),
+            GraphNode::new(2, "C".to_string()),
+        ];
+        let graph = Graph::new(graph_nodes.clone(), vec![Edge::new(0, 1, 3)]).unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        assert_eq!(
+            context.bidirectional_dijkstra(&graph, 0, 2),
+            Err(DijkstraError::Disconnected {
+                start_idx: 0,
+                end_idx: 2,
+                start_name: "A".to_string(),
+                end_name: "C".to_string()
+            })
+        );
+
+        let graph = Graph::new(
+            graph_nodes,
+            vec![Edge::new(0, 1, usize::MAX), Edge::new(1, 2, 1)],
+        )
+        .unwrap();
+        assert_eq!(
+            context.bidirectional_dijkstra(&graph, 0, 2),
+            Err(DijkstraError::DistanceOverflow {
+                start_idx: 0,
+                end_idx: 2,
+                start_name: "A".to_string(),
+                end_name: "C".to_string()
+            })
+        );
+        assert_eq!(
+            context.bidirectional_dijkstra(&graph, 0, 1),
+            Ok((usize::MAX, vec![0, 1]))
+        );
+    }
+}
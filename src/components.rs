@@ -0,0 +1,195 @@
+use crate::construct_graph::Graph;
+use crate::weight::Weight;
+
+use std::collections::VecDeque;
+
+impl<W: Weight> Graph<W> {
+    /// the nodes that can be reached from the node along the edges, including the node itself,
+    /// in index order
+    pub fn reachable_from(&self, node_idx: usize) -> Vec<usize> {
+        let mut is_reached = vec![false; self.number_of_nodes];
+        is_reached[node_idx] = true;
+        let mut to_visit = vec![node_idx];
+        while let Some(current_idx) = to_visit.pop() {
+            for edge in &self.connections[current_idx] {
+                if !is_reached[edge.index_second] {
+                    is_reached[edge.index_second] = true;
+                    to_visit.push(edge.index_second);
+                }
+            }
+        }
+        return (0..self.number_of_nodes)
+            .filter(|&idx| is_reached[idx])
+            .collect();
+    }
+
+    /// the pieces the graph is split into, ignoring the direction of directed edges. Each
+    /// component is in index order, and the components are ordered by their first node.
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        let mut component_of: Vec<Option<usize>> = vec![None; self.number_of_nodes];
+        let mut components = Vec::new();
+        for root_idx in 0..self.number_of_nodes {
+            if component_of[root_idx].is_some() {
+                continue;
+            }
+            let mut component = vec![root_idx];
+            component_of[root_idx] = Some(components.len());
+            let mut to_visit = VecDeque::from([root_idx]);
+            while let Some(current_idx) = to_visit.pop_front() {
+                let forwards = self.connections[current_idx]
+                    .iter()
+                    .map(|edge| edge.index_second);
+                let backwards = self.reverse_connections[current_idx]
+                    .iter()
+                    .map(|edge| edge.index_first);
+                for next_idx in forwards.chain(backwards) {
+                    if component_of[next_idx].is_none() {
+                        component_of[next_idx] = Some(components.len());
+                        component.push(next_idx);
+                        to_visit.push_back(next_idx);
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        return components;
+    }
+
+    /// Tarjan's algorithm: the groups of nodes that can all reach each other along the edges.
+    /// Each component is in index order, and the components are ordered by their first node.
+    /// Without directed edges these are the same as the connected components.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let number_of_nodes = self.number_of_nodes;
+        // `order[node_idx]` is when the depth first search first reached the node
+        let mut order: Vec<Option<usize>> = vec![None; number_of_nodes];
+        // the earliest node that can be reached from the node's subtree and is still on the stack
+        let mut low_link = vec![0; number_of_nodes];
+        let mut is_on_stack = vec![false; number_of_nodes];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_order = 0;
+
+        for root_idx in 0..number_of_nodes {
+            if order[root_idx].is_some() {
+                continue;
+            }
+            // the depth first search path, with how many of each node's edges have been followed
+            let mut path = vec![(root_idx, 0)];
+            order[root_idx] = Some(next_order);
+            low_link[root_idx] = next_order;
+            next_order += 1;
+            stack.push(root_idx);
+            is_on_stack[root_idx] = true;
+
+            while let Some(&mut (current_idx, ref mut edges_followed)) = path.last_mut() {
+                if let Some(edge) = self.connections[current_idx].get(*edges_followed) {
+                    *edges_followed += 1;
+                    let next_idx = edge.index_second;
+                    match order[next_idx] {
+                        None => {
+                            order[next_idx] = Some(next_order);
+                            low_link[next_idx] = next_order;
+                            next_order += 1;
+                            stack.push(next_idx);
+                            is_on_stack[next_idx] = true;
+                            path.push((next_idx, 0));
+                        }
+                        Some(reached_order) if is_on_stack[next_idx] => {
+                            low_link[current_idx] = low_link[current_idx].min(reached_order);
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+
+                // every edge has been followed, so the node's subtree is finished
+                path.pop();
+                if let Some(&(parent_idx, _)) = path.last() {
+                    low_link[parent_idx] = low_link[parent_idx].min(low_link[current_idx]);
+                }
+                if Some(low_link[current_idx]) == order[current_idx] {
+                    let mut component = Vec::new();
+                    while let Some(node_idx) = stack.pop() {
+                        is_on_stack[node_idx] = false;
+                        component.push(node_idx);
+                        if node_idx == current_idx {
+                            break;
+                        }
+                    }
+                    component.sort();
+                    components.push(component);
+                }
+            }
+        }
+        components.sort();
+        return components;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::construct_graph::Graph;
+
+    use std::fs;
+
+    #[test]
+    fn connected_components_ignore_direction() {
+        let graph = Graph::new_from_string(
+            "6\nA\nB\nC\nD\nE\nF\n\n4\nA -> B 1\nC -> B 1\nD E 1\nE -> D 1\n\nA B",
+        )
+        .unwrap();
+        assert_eq!(
+            graph.connected_components(),
+            vec![vec![0, 1, 2], vec![3, 4], vec![5]]
+        );
+        assert_eq!(
+            graph.strongly_connected_components(),
+            vec![vec![0], vec![1], vec![2], vec![3, 4], vec![5]]
+        );
+        assert_eq!(graph.reachable_from(0), vec![0, 1]);
+        assert_eq!(graph.reachable_from(1), vec![1]);
+        assert_eq!(graph.reachable_from(4), vec![3, 4]);
+    }
+    #[test]
+    fn strongly_connected_components_of_cycles() {
+        // two cycles joined one way, and a node that only leads into them
+        let graph = Graph::new_from_string(
+            "7\nA\nB\nC\nD\nE\nF\nG\n\n8 directed\nA B 1\nB C 1\nC A 1\nC D 1\nD E 1\nE F 1\nF D 1\nG A 1\n\nA F",
+        )
+        .unwrap();
+        assert_eq!(
+            graph.strongly_connected_components(),
+            vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]
+        );
+        assert_eq!(
+            graph.connected_components(),
+            vec![(0..7).collect::<Vec<usize>>()]
+        );
+        assert_eq!(graph.reachable_from(3), vec![3, 4, 5]);
+        assert_eq!(graph.reachable_from(6), (0..7).collect::<Vec<usize>>());
+    }
+    #[test]
+    fn components_match_reachability() {
+        let contents = fs::read_to_string("src/test/rebates.txt").unwrap();
+        let graph = Graph::<i64>::new_from_string_with_signed_weights(&contents).unwrap();
+        // nodes are in the same strongly connected component if they can reach each other
+        let components = graph.strongly_connected_components();
+        for a_idx in 0..graph.number_of_nodes {
+            for b_idx in 0..graph.number_of_nodes {
+                let can_reach_each_other = graph.reachable_from(a_idx).contains(&b_idx)
+                    && graph.reachable_from(b_idx).contains(&a_idx);
+                let are_in_same_component = components
+                    .iter()
+                    .any(|component| component.contains(&a_idx) && component.contains(&b_idx));
+                assert_eq!(can_reach_each_other, are_in_same_component);
+            }
+        }
+        let uk = Graph::new_from_string(&fs::read_to_string("src/test/uk.txt").unwrap()).unwrap();
+        assert_eq!(
+            uk.strongly_connected_components(),
+            uk.connected_components()
+        );
+        assert_eq!(uk.connected_components().len(), 1);
+    }
+}
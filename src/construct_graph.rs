@@ -1,43 +1,202 @@
+use crate::error::DijkstraError;
+use crate::json::JsonGraph;
 use crate::parse_input::{
-    parse_edges_from_string, parse_graph_nodes_from_string, split_contents_into_nodes_edges_routes,
-    Edge, GraphNode,
+    lines_before_edges_and_routes, parse_edges_from_string, parse_graph_nodes_from_string,
+    parse_signed_edges_from_string, split_contents_into_nodes_edges_routes, Edge, GraphNode,
 };
-pub const INFINITE_DIST: usize = 100000000;
+use crate::weight::Weight;
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Graph {
-    pub number_of_nodes: usize,
-    pub edges: Vec<Edge>,
-    pub graph_nodes: Vec<GraphNode>,
+use serde::{Deserialize, Serialize};
+
+/// The graph is never changed once constructed, so it can be shared between any number of
+/// searches, i.e. as `&Graph` or `Arc<Graph>`. Per-query state lives in a `SearchContext`.
+/// With serde it is read and written in the JSON form of `src/json.rs`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(
+    into = "JsonGraph<W>",
+    try_from = "JsonGraph<W>",
+    bound(
+        serialize = "W: Weight + Serialize",
+        deserialize = "W: Weight + Deserialize<'de>"
+    )
+)]
+pub struct Graph<W = usize> {
+    pub(crate) number_of_nodes: usize,
+    pub(crate) edges: Vec<Edge<W>>,
+    pub(crate) graph_nodes: Vec<GraphNode>,
+    /// `connections[node_idx]` holds every edge leaving that node, with the minimum weight kept
+    /// for repeated edges
+    pub(crate) connections: Vec<Vec<Edge<W>>>,
+    /// `reverse_connections[node_idx]` holds every edge of `connections` arriving at that node,
+    /// for searching backwards from the end of a route
+    pub(crate) reverse_connections: Vec<Vec<Edge<W>>>,
 }
 
 impl Graph {
-    pub(crate) fn new(graph_nodes: Vec<GraphNode>, edges_: Vec<Edge>) -> Graph {
+    /// read a graph with integer weights from the text format, see `new_from_string_with_weights`
+    pub fn new_from_string(contents: &str) -> Result<Graph, DijkstraError> {
+        return Graph::new_from_string_with_weights(contents);
+    }
+}
+
+impl<W: Weight> Graph<W> {
+    /// the nodes are numbered by their position in `graph_nodes`, which must be their `index`,
+    /// and every edge must join two of them. The weights can't be negative.
+    pub fn new(
+        graph_nodes: Vec<GraphNode>,
+        edges_: Vec<Edge<W>>,
+    ) -> Result<Graph<W>, DijkstraError> {
+        return Graph::new_allowing_negative_weights(graph_nodes, edges_, false);
+    }
+
+    /// as new, but negative weights are allowed, for use with `bellman_ford`
+    pub fn new_with_signed_weights(
+        graph_nodes: Vec<GraphNode>,
+        edges_: Vec<Edge<W>>,
+    ) -> Result<Graph<W>, DijkstraError> {
+        return Graph::new_allowing_negative_weights(graph_nodes, edges_, true);
+    }
+
+    pub(crate) fn new_allowing_negative_weights(
+        graph_nodes: Vec<GraphNode>,
+        edges_: Vec<Edge<W>>,
+        allow_negative_weights: bool,
+    ) -> Result<Graph<W>, DijkstraError> {
         let num_nodes = graph_nodes.len();
+        // names are looked up by the index of the node, so it must match its position
+        for (node_idx, node) in graph_nodes.iter().enumerate() {
+            if node.index != node_idx {
+                return Err(DijkstraError::InvalidNodeIndex {
+                    node_idx: node.index,
+                });
+            }
+        }
+        for edge in &edges_ {
+            for node_idx in [edge.index_first, edge.index_second] {
+                if node_idx >= num_nodes {
+                    return Err(DijkstraError::InvalidNodeIndex { node_idx });
+                }
+            }
+            if !allow_negative_weights && edge.weight < W::zero() {
+                return Err(DijkstraError::NegativeEdgeWeight {
+                    from_name: graph_nodes[edge.index_first].node_name.to_string(),
+                    to_name: graph_nodes[edge.index_second].node_name.to_string(),
+                    weight: edge.weight.to_string(),
+                });
+            }
+        }
+
+        let mut connections: Vec<Vec<Edge<W>>> = Vec::with_capacity(num_nodes);
+        for _ in 0..num_nodes {
+            connections.push(Vec::new());
+        }
+        for edge in &edges_ {
+            Self::update_edge_in_both_directions(&mut connections, *edge);
+        }
+        let mut reverse_connections: Vec<Vec<Edge<W>>> = vec![Vec::new(); num_nodes];
+        for edge in connections.iter().flatten() {
+            reverse_connections[edge.index_second].push(*edge);
+        }
 
         let graph = Graph {
             number_of_nodes: num_nodes,
             edges: edges_,
             graph_nodes,
+            connections,
+            reverse_connections,
         };
 
-        return graph;
+        return Ok(graph);
+    }
+
+    pub fn number_of_nodes(&self) -> usize {
+        return self.number_of_nodes;
+    }
+
+    /// the edges as they were given, including repeated edges
+    pub fn edges(&self) -> &[Edge<W>] {
+        return &self.edges;
     }
 
-    pub(crate) fn new_from_string(contents: &str) -> Result<Graph, String> {
+    pub fn graph_nodes(&self) -> &[GraphNode] {
+        return &self.graph_nodes;
+    }
+
+    /// every edge leaving the node, with the minimum weight kept for repeated edges
+    pub fn connections(&self, node_idx: usize) -> &[Edge<W>] {
+        return &self.connections[node_idx];
+    }
+
+    /// every edge of `connections` arriving at the node
+    pub fn reverse_connections(&self, node_idx: usize) -> &[Edge<W>] {
+        return &self.reverse_connections[node_idx];
+    }
+
+    /// read a graph with weights of type W from the text format, i.e.
+    /// `Graph::<TotalF64>::new_from_string_with_weights` for decimal weights.
+    /// Errors report the line number within the whole of the contents.
+    pub fn new_from_string_with_weights(contents: &str) -> Result<Graph<W>, DijkstraError> {
+        return Graph::new_from_string_allowing_negative_weights(contents, false);
+    }
+
+    /// as new_from_string_with_weights, but negative weights are allowed, i.e.
+    /// `Graph::<i64>::new_from_string_with_signed_weights` for use with `bellman_ford`
+    pub fn new_from_string_with_signed_weights(contents: &str) -> Result<Graph<W>, DijkstraError> {
+        return Graph::new_from_string_allowing_negative_weights(contents, true);
+    }
+
+    pub(crate) fn new_from_string_allowing_negative_weights(
+        contents: &str,
+        allow_negative_weights: bool,
+    ) -> Result<Graph<W>, DijkstraError> {
         let (node_data, edge_data, _) =
             split_contents_into_nodes_edges_routes(contents.to_string())?;
+        let (lines_before_edges, _) = lines_before_edges_and_routes(&node_data, &edge_data);
         let graph_nodes = parse_graph_nodes_from_string(&node_data)?;
-        let edges = parse_edges_from_string(&edge_data, &graph_nodes)?;
-        let graph = Graph::new(graph_nodes, edges);
+        let edges = if allow_negative_weights {
+            parse_signed_edges_from_string(&edge_data, &graph_nodes)
+        } else {
+            parse_edges_from_string(&edge_data, &graph_nodes)
+        }
+        .map_err(|e| e.offset_line(lines_before_edges))?;
+        return Graph::new_allowing_negative_weights(graph_nodes, edges, allow_negative_weights);
+    }
 
-        return Ok(graph);
+    fn update_edge_in_both_directions(connections: &mut [Vec<Edge<W>>], new_edge: Edge<W>) {
+        Self::update_existing_edge(connections, new_edge);
+        if !new_edge.is_directed {
+            let new_reverse_edge = Edge {
+                index_first: new_edge.index_second,
+                index_second: new_edge.index_first,
+                ..new_edge
+            };
+            Self::update_existing_edge(connections, new_reverse_edge);
+        }
+    }
+
+    /// if the input has the edge a->b more than once, choose the minimum weight
+    fn update_existing_edge(connections: &mut [Vec<Edge<W>>], new_edge: Edge<W>) -> bool {
+        let start_index = new_edge.index_first;
+        let edge_index = connections[start_index]
+            .iter()
+            .position(|x| x.index_second == new_edge.index_second);
+        if let Some(idx_into_edge_list) = edge_index {
+            let old_edge_weight = connections[start_index][idx_into_edge_list].weight;
+            if old_edge_weight < new_edge.weight {
+                return false;
+            }
+            connections[start_index].remove(idx_into_edge_list);
+        }
+        connections[start_index].push(new_edge);
+        return true;
     }
 }
 
 #[cfg(test)]
 mod graph_only_tests {
     use crate::construct_graph::Graph;
+    use crate::error::DijkstraError;
+    use crate::parse_input::{Edge, GraphNode};
 
     #[test]
     fn test_route_finding_with_incorrect_number_of_nodes() {
@@ -46,7 +205,11 @@ mod graph_only_tests {
         );
 
         assert_eq!(
-            Err("Unexpected number of edges. Expected: 4, actual: 5".to_string()),
+            Err(DijkstraError::UnexpectedEdgeCount {
+                line: 7,
+                expected: 4,
+                actual: 5
+            }),
             graph
         )
     }
@@ -55,8 +218,80 @@ mod graph_only_tests {
         let graph =
             Graph::new_from_string("4\nA\nB\nC\nD\n\n4\nI G 167\nI E 158\nG E 45\nI N 17\n\nA B");
         assert_eq!(
-            Err("Nodes in edges should be present in node list. Node I (possibly others) not found.".to_string()),
+            Err(DijkstraError::UnknownNode {
+                line: 8,
+                text: "I G 167".to_string(),
+                node_name: "I".to_string()
+            }),
             graph
         )
     }
+    #[test]
+    fn test_connections_keep_minimum_weight_in_both_directions() {
+        let graph =
+            Graph::new_from_string("3\nA\nB\nC\n\n4\nA B 20\nB A 2\nA B 5\nB C 3\n\nA C").unwrap();
+        assert_eq!(graph.connections[0], vec![Edge::new(0, 1, 2)]);
+        assert_eq!(
+            graph.connections[1],
+            vec![Edge::new(1, 0, 2), Edge::new(1, 2, 3)]
+        );
+        assert_eq!(graph.connections[2], vec![Edge::new(2, 1, 3)]);
+    }
+    #[test]
+    fn test_directed_connections() {
+        let graph = Graph::new_from_string("3\nA\nB\nC\n\n3\nA -> B 2\nA <-> B 5\nB -> C 3\n\nA C")
+            .unwrap();
+        assert_eq!(graph.connections[0], vec![Edge::new_directed(0, 1, 2)]);
+        // the bidirectional edge still allows B->A, even though its A->B is longer than A -> B
+        assert_eq!(
+            graph.connections[1],
+            vec![Edge::new(1, 0, 5), Edge::new_directed(1, 2, 3)]
+        );
+        assert!(graph.connections[2].is_empty());
+        assert_eq!(graph.reverse_connections[0], vec![Edge::new(1, 0, 5)]);
+        assert_eq!(
+            graph.reverse_connections[1],
+            vec![Edge::new_directed(0, 1, 2)]
+        );
+        assert_eq!(
+            graph.reverse_connections[2],
+            vec![Edge::new_directed(1, 2, 3)]
+        );
+    }
+    #[test]
+    fn test_edges_must_join_nodes_of_the_graph() {
+        let graph_nodes = vec![GraphNode::new(0, "A".to_string())];
+        assert_eq!(
+            Graph::new(graph_nodes, vec![Edge::new(0, 3, 1)]),
+            Err(DijkstraError::InvalidNodeIndex { node_idx: 3 })
+        );
+    }
+    #[test]
+    fn test_nodes_must_be_numbered_by_position() {
+        let graph_nodes = vec![
+            GraphNode::new(0, "A".to_string()),
+            GraphNode::new(0, "B".to_string()),
+        ];
+        assert_eq!(
+            Graph::new(graph_nodes, vec![Edge::<usize>::new(0, 1, 1)]),
+            Err(DijkstraError::InvalidNodeIndex { node_idx: 0 })
+        );
+    }
+    #[test]
+    fn test_weights_are_only_negative_when_signed() {
+        let graph_nodes = vec![
+            GraphNode::new(0, "A".to_string()),
+            GraphNode::new(1, "B".to_string()),
+        ];
+        let edges = vec![Edge::<i64>::new(0, 1, -5)];
+        assert_eq!(
+            Graph::new(graph_nodes.clone(), edges.clone()),
+            Err(DijkstraError::NegativeEdgeWeight {
+                from_name: "A".to_string(),
+                to_name: "B".to_string(),
+                weight: "-5".to_string()
+            })
+        );
+        assert!(Graph::new_with_signed_weights(graph_nodes, edges).is_ok());
+    }
 }
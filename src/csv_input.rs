@@ -0,0 +1,529 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::find_path::{Algorithm, PathFinder};
+use crate::parse_input::{
+    get_node_index_from_node_name, parse_weight_on_line, Edge, GraphNode, RouteQuery,
+};
+use crate::route::RouteOutcome;
+use crate::weight::Weight;
+
+use csv::{ErrorKind, Position, ReaderBuilder, StringRecord, Trim};
+
+/// The names of the columns to read from CSV files, i.e. from a spreadsheet.
+/// Each file has a header row, and columns that aren't named here are ignored, except in the
+/// nodes file where they are kept as the node's `attributes`.
+/// To change only some of the names, start from the defaults, i.e.
+/// `CsvColumns { weight: "km".to_string(), ..CsvColumns::default() }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvColumns {
+    /// the node name, in the nodes file
+    pub name: String,
+    /// the optional coordinates of the node, in the nodes file
+    pub x: String,
+    pub y: String,
+    /// the node each edge starts at, in the edges file
+    pub source: String,
+    /// the node each edge ends at, in the edges file
+    pub target: String,
+    pub weight: String,
+    /// optional, `true` or `false` for each edge. Edges are bidirectional without it.
+    pub directed: String,
+    /// the start and end of each route, in the routes file
+    pub route_start: String,
+    pub route_end: String,
+    /// optional, how many of the shortest routes to find, as in `A B 3`
+    pub number_of_routes: String,
+}
+
+impl Default for CsvColumns {
+    fn default() -> CsvColumns {
+        return CsvColumns {
+            name: "name".to_string(),
+            x: "x".to_string(),
+            y: "y".to_string(),
+            source: "source".to_string(),
+            target: "target".to_string(),
+            weight: "weight".to_string(),
+            directed: "directed".to_string(),
+            route_start: "from".to_string(),
+            route_end: "to".to_string(),
+            number_of_routes: "k".to_string(),
+        };
+    }
+}
+
+impl<W: Weight> Graph<W> {
+    /// read a graph from an edges CSV, and optionally a nodes CSV. Without a nodes file the
+    /// nodes are the names in the source and target columns, in the order they first appear.
+    /// The weights can't be negative. Errors give the row, counting the header as row 1.
+    pub fn new_from_csv(
+        nodes: Option<&str>,
+        edges: &str,
+        columns: &CsvColumns,
+    ) -> Result<Graph<W>, DijkstraError> {
+        return Graph::new_from_csv_allowing_negative_weights(nodes, edges, columns, false);
+    }
+
+    /// as new_from_csv, but negative weights are allowed, for use with `bellman_ford`
+    pub fn new_from_csv_with_signed_weights(
+        nodes: Option<&str>,
+        edges: &str,
+        columns: &CsvColumns,
+    ) -> Result<Graph<W>, DijkstraError> {
+        return Graph::new_from_csv_allowing_negative_weights(nodes, edges, columns, true);
+    }
+
+    fn new_from_csv_allowing_negative_weights(
+        nodes: Option<&str>,
+        edges: &str,
+        columns: &CsvColumns,
+        allow_negative_weights: bool,
+    ) -> Result<Graph<W>, DijkstraError> {
+        let mut graph_nodes = match nodes {
+            Some(nodes) => parse_csv_nodes(nodes, columns)?,
+            None => Vec::new(),
+        };
+        let edges = parse_csv_edges(
+            edges,
+            &mut graph_nodes,
+            nodes.is_none(),
+            columns,
+            allow_negative_weights,
+        )?;
+        return Graph::new_allowing_negative_weights(graph_nodes, edges, allow_negative_weights);
+    }
+}
+
+impl<W: Weight> PathFinder<W> {
+    /// create the PathFinder from CSV files, see `Graph::new_from_csv`. The number of routes to
+    /// find is dropped.
+    pub fn new_from_csv(
+        nodes: Option<&str>,
+        edges: &str,
+        routes: &str,
+        columns: &CsvColumns,
+    ) -> Result<PathFinder<W>, DijkstraError> {
+        let graph = Graph::new_from_csv(nodes, edges, columns)?;
+        let routes_to_find = parse_csv_routes(routes, &graph.graph_nodes, columns)?
+            .into_iter()
+            .map(|query| query.map(|query| (query.start_idx, query.end_idx)))
+            .collect::<Result<Vec<(usize, usize)>, DijkstraError>>()?;
+        return PathFinder::new(graph, routes_to_find);
+    }
+
+    /// batch mode for CSV files, as `solve_batch_from_string_using`. Only a problem with the
+    /// nodes, the edges or the header of the routes is an error, every other row of the routes
+    /// gets its own outcome.
+    pub fn solve_batch_from_csv_using(
+        nodes: Option<&str>,
+        edges: &str,
+        routes: &str,
+        columns: &CsvColumns,
+        algorithm: Algorithm<W>,
+    ) -> Result<Vec<RouteOutcome<W>>, DijkstraError> {
+        let allow_negative_weights = matches!(algorithm, Algorithm::BellmanFord);
+        let graph = Graph::new_from_csv_allowing_negative_weights(
+            nodes,
+            edges,
+            columns,
+            allow_negative_weights,
+        )?;
+        let route_queries = parse_csv_routes(routes, &graph.graph_nodes, columns)?;
+        return Ok(PathFinder::solve_batch(graph, route_queries, algorithm));
+    }
+}
+
+/// a node is a name, with optional x and y coordinates. Every other column is an attribute.
+fn parse_csv_nodes(contents: &str, columns: &CsvColumns) -> Result<Vec<GraphNode>, DijkstraError> {
+    let (header, rows) = csv_rows(contents)?;
+    let name_pos = column_position(&header, &columns.name)?;
+    let x_pos = optional_column_position(&header, &columns.x);
+    let y_pos = optional_column_position(&header, &columns.y);
+
+    let mut graph_nodes = Vec::new();
+    for row in rows {
+        let (line, record) = row?;
+        let text = row_text(&record);
+        let invalid_node = || DijkstraError::InvalidNode {
+            line,
+            text: text.clone(),
+        };
+        let name = &record[name_pos];
+        if name.is_empty() {
+            return Err(invalid_node());
+        }
+        let idx = graph_nodes.len();
+        let coordinates = (x_pos.map(|pos| &record[pos]), y_pos.map(|pos| &record[pos]));
+        let mut node = match coordinates {
+            (None | Some(""), None | Some("")) => GraphNode::new(idx, name.to_string()),
+            (Some(x), Some(y)) => match (x.parse::<f64>(), y.parse::<f64>()) {
+                (Ok(x), Ok(y)) if x.is_finite() && y.is_finite() => {
+                    GraphNode::new_with_coordinates(idx, name.to_string(), x, y)
+                }
+                _ => return Err(invalid_node()),
+            },
+            _ => return Err(invalid_node()),
+        };
+        for (pos, value) in record.iter().enumerate() {
+            if pos != name_pos && Some(pos) != x_pos && Some(pos) != y_pos {
+                node.attributes
+                    .insert(header[pos].to_string(), value.to_string());
+            }
+        }
+        graph_nodes.push(node);
+    }
+    return Ok(graph_nodes);
+}
+
+/// the edges, checked as in `parse_edges_from_string`. If the nodes are inferred, a name that
+/// isn't in the node list is added to it rather than being an error.
+fn parse_csv_edges<W: Weight>(
+    contents: &str,
+    graph_nodes: &mut Vec<GraphNode>,
+    infer_nodes: bool,
+    columns: &CsvColumns,
+    allow_negative_weights: bool,
+) -> Result<Vec<Edge<W>>, DijkstraError> {
+    let (header, rows) = csv_rows(contents)?;
+    let source_pos = column_position(&header, &columns.source)?;
+    let target_pos = column_position(&header, &columns.target)?;
+    let weight_pos = column_position(&header, &columns.weight)?;
+    let directed_pos = optional_column_position(&header, &columns.directed);
+
+    let mut edges = Vec::new();
+    for row in rows {
+        let (line, record) = row?;
+        let text = row_text(&record);
+        let invalid_edge = || DijkstraError::InvalidEdge {
+            line,
+            text: text.clone(),
+        };
+        let is_directed = match directed_pos
+            .map(|pos| record[pos].to_lowercase())
+            .as_deref()
+        {
+            None | Some("" | "false" | "no" | "0") => false,
+            Some("true" | "yes" | "1") => true,
+            Some(_) => return Err(invalid_edge()),
+        };
+        let weight =
+            parse_weight_on_line(&record[weight_pos], allow_negative_weights, line, &text)?;
+
+        let mut node_indexes = [0; 2];
+        for (node_idx, pos) in node_indexes.iter_mut().zip([source_pos, target_pos]) {
+            let node_name = &record[pos];
+            if node_name.is_empty() {
+                return Err(invalid_edge());
+            }
+            *node_idx = match get_node_index_from_node_name(node_name, graph_nodes) {
+                Some(node_idx) => node_idx,
+                None if infer_nodes => {
+                    graph_nodes.push(GraphNode::new(graph_nodes.len(), node_name.to_string()));
+                    graph_nodes.len() - 1
+                }
+                None => {
+                    return Err(DijkstraError::UnknownNode {
+                        line,
+                        text,
+                        node_name: node_name.to_string(),
+                    })
+                }
+            };
+        }
+        let [start_idx, end_idx] = node_indexes;
+
+        // as in the text format, an edge from a node to itself is never useful
+        if start_idx != end_idx {
+            if is_directed {
+                edges.push(Edge::new_directed(start_idx, end_idx, weight));
+            } else {
+                edges.push(Edge::new(start_idx, end_idx, weight));
+            }
+        }
+    }
+    return Ok(edges);
+}
+
+/// each row gets its own result, as in `parse_route_queries_from_string`. Only a problem with
+/// the header is an error for every route.
+fn parse_csv_routes(
+    contents: &str,
+    graph_nodes: &[GraphNode],
+    columns: &CsvColumns,
+) -> Result<Vec<Result<RouteQuery, DijkstraError>>, DijkstraError> {
+    let (header, rows) = csv_rows(contents)?;
+    let start_pos = column_position(&header, &columns.route_start)?;
+    let end_pos = column_position(&header, &columns.route_end)?;
+    let number_of_routes_pos = optional_column_position(&header, &columns.number_of_routes);
+
+    let parse_route = |(line, record): (usize, StringRecord)| {
+        let text = row_text(&record);
+        let invalid_route = || DijkstraError::InvalidRoute {
+            line,
+            text: text.clone(),
+        };
+        let (start_str, end_str) = (&record[start_pos], &record[end_pos]);
+        if start_str.is_empty() || end_str.is_empty() {
+            return Err(invalid_route());
+        }
+        let number_of_routes = match number_of_routes_pos.map(|pos| &record[pos]) {
+            None | Some("") => None,
+            Some(k) => match k.parse::<usize>() {
+                Ok(k) if k > 0 => Some(k),
+                _ => return Err(invalid_route()),
+            },
+        };
+        if start_str == end_str {
+            return Err(DijkstraError::SelfReferentialRoute {
+                line,
+                text,
+                node_name: start_str.to_string(),
+            });
+        }
+        let find_node = |node_name: &str| {
+            return get_node_index_from_node_name(node_name, graph_nodes).ok_or_else(|| {
+                DijkstraError::UnknownNode {
+                    line,
+                    text: text.clone(),
+                    node_name: node_name.to_string(),
+                }
+            });
+        };
+        return Ok(RouteQuery {
+            start_idx: find_node(start_str)?,
+            end_idx: find_node(end_str)?,
+            number_of_routes,
+        });
+    };
+    return Ok(rows.map(|row| row.and_then(parse_route)).collect());
+}
+
+/// a row of a CSV file after the header, with its row number
+type CsvRow = Result<(usize, StringRecord), DijkstraError>;
+
+/// the header row, and the other rows with their row number. Surrounding spaces are trimmed and
+/// empty rows are skipped, but still counted.
+fn csv_rows(
+    contents: &str,
+) -> Result<(StringRecord, impl Iterator<Item = CsvRow> + '_), DijkstraError> {
+    let mut reader = ReaderBuilder::new()
+        .trim(Trim::All)
+        .from_reader(contents.as_bytes());
+    let header = reader
+        .headers()
+        .map_err(|e| csv_error(e, &mut RowCounter::new(contents)))?
+        .clone();
+    let mut rows = RowCounter::new(contents);
+    let rows = reader.into_records().map(move |record| {
+        let record = record.map_err(|e| csv_error(e, &mut rows))?;
+        let line = rows.row_at(record.position());
+        return Ok((line, record));
+    });
+    return Ok((header, rows));
+}
+
+/// The csv reader gives the position of a record before the empty rows it skips, so the rows are
+/// counted from the byte offset of each record instead, carrying on from the previous record.
+struct RowCounter<'a> {
+    contents: &'a [u8],
+    byte: usize,
+    row: usize,
+}
+
+impl<'a> RowCounter<'a> {
+    fn new(contents: &'a str) -> RowCounter<'a> {
+        return RowCounter {
+            contents: contents.as_bytes(),
+            byte: 0,
+            row: 1,
+        };
+    }
+
+    /// the row at the position, which can't be before the previous position
+    fn row_at(&mut self, position: Option<&Position>) -> usize {
+        if let Some(position) = position {
+            let mut byte = (position.byte() as usize).min(self.contents.len());
+            // the empty rows skipped before the record
+            while let Some(b'\r' | b'\n') = self.contents.get(byte) {
+                byte += 1;
+            }
+            self.row += self.contents[self.byte..byte]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count();
+            self.byte = byte;
+        }
+        return self.row;
+    }
+}
+
+fn column_position(header: &StringRecord, column: &str) -> Result<usize, DijkstraError> {
+    return optional_column_position(header, column).ok_or_else(|| DijkstraError::InvalidCsv {
+        line: 1,
+        message: format!("there is no {:?} column in the header", column),
+    });
+}
+
+fn optional_column_position(header: &StringRecord, column: &str) -> Option<usize> {
+    return header.iter().position(|name| name == column);
+}
+
+/// the row as it would be written, for the error messages
+fn row_text(record: &StringRecord) -> String {
+    return record.iter().collect::<Vec<&str>>().join(",");
+}
+
+fn csv_error(e: csv::Error, rows: &mut RowCounter) -> DijkstraError {
+    let line = rows.row_at(e.position());
+    let message = match e.kind() {
+        ErrorKind::UnequalLengths {
+            expected_len, len, ..
+        } => format!("expected {} fields but {} found", expected_len, len),
+        _ => e.to_string(),
+    };
+    return DijkstraError::InvalidCsv { line, message };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weight::TotalF64;
+
+    use std::fs;
+
+    #[test]
+    fn read_nodes_edges_and_routes() {
+        let nodes = fs::read_to_string("src/test/csv/uk_nodes.csv").unwrap();
+        let edges = fs::read_to_string("src/test/csv/uk_edges.csv").unwrap();
+        let routes = fs::read_to_string("src/test/csv/uk_routes.csv").unwrap();
+        let columns = CsvColumns {
+            source: "from".to_string(),
+            target: "to".to_string(),
+            weight: "miles".to_string(),
+            ..CsvColumns::default()
+        };
+        let graph = Graph::<usize>::new_from_csv(Some(&nodes), &edges, &columns).unwrap();
+        let text = Graph::new_from_string(&fs::read_to_string("src/test/uk.txt").unwrap()).unwrap();
+        assert_eq!(graph.connections, text.connections);
+        assert_eq!(graph.graph_nodes[9].coordinates, Some((530.0, 180.0)));
+        assert_eq!(
+            graph.graph_nodes[7]
+                .attributes
+                .get("country")
+                .map(|c| c.as_str()),
+            Some("Wales")
+        );
+
+        let route_columns = CsvColumns {
+            route_start: "start".to_string(),
+            route_end: "end".to_string(),
+            ..columns.clone()
+        };
+        let path_finder =
+            PathFinder::<usize>::new_from_csv(Some(&nodes), &edges, &routes, &route_columns)
+                .unwrap();
+        assert_eq!(
+            path_finder.routes_to_find,
+            vec![(1, 2), (7, 9), (5, 6), (9, 0)]
+        );
+    }
+    #[test]
+    fn infer_nodes_from_edges() {
+        let edges = "source,target,weight,directed\nA,B,1.5,\nC,A,2,true\nB,D,0.5,FALSE\n";
+        let graph = Graph::<TotalF64>::new_from_csv(None, edges, &CsvColumns::default()).unwrap();
+        let names: Vec<&str> = graph
+            .graph_nodes
+            .iter()
+            .map(|node| node.node_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["A", "B", "C", "D"]);
+        assert_eq!(graph.graph_nodes[3].index, 3);
+        assert_eq!(
+            graph.edges[1],
+            Edge::new_directed(2, 0, TotalF64::new(2.0).unwrap())
+        );
+        assert!(!graph.edges[2].is_directed);
+
+        let routes = "from,to,k\nC,D,2\nD,C,\nA,E,\n";
+        let outcomes = PathFinder::<TotalF64>::solve_batch_from_csv_using(
+            None,
+            edges,
+            routes,
+            &CsvColumns::default(),
+            Algorithm::Dijkstra,
+        )
+        .unwrap();
+        let outcomes: Vec<String> = outcomes.iter().map(|outcome| outcome.to_string()).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                "1. C->A->B->D, dist 4",
+                "D->C, no path found. Are the start and end disconnected?",
+                "Line 4: node E is not in the node list."
+            ]
+        );
+    }
+    #[test]
+    fn errors_give_the_row() {
+        let columns = CsvColumns::default();
+        let read = |edges: &str| Graph::<usize>::new_from_csv(None, edges, &columns);
+        assert_eq!(
+            read("source,target,weight\nA,B,1\n\nB,C,-2\n"),
+            Err(DijkstraError::NegativeWeight {
+                line: 4,
+                text: "B,C,-2".to_string(),
+                weight: "-2".to_string()
+            })
+        );
+        assert_eq!(
+            read("source,target,weight\nA,B,1\nB,,2\n"),
+            Err(DijkstraError::InvalidEdge {
+                line: 3,
+                text: "B,,2".to_string()
+            })
+        );
+        assert_eq!(
+            read("source,target,weight\nA,B,1\nB,C\n"),
+            Err(DijkstraError::InvalidCsv {
+                line: 3,
+                message: "expected 3 fields but 2 found".to_string()
+            })
+        );
+        assert_eq!(
+            read("from,to,weight\nA,B,1\n"),
+            Err(DijkstraError::InvalidCsv {
+                line: 1,
+                message: "there is no \"source\" column in the header".to_string()
+            })
+        );
+        assert_eq!(
+            Graph::<usize>::new_from_csv(
+                Some("name\nA\nB"),
+                "source,target,weight\nA,C,1",
+                &columns
+            ),
+            Err(DijkstraError::UnknownNode {
+                line: 2,
+                text: "A,C,1".to_string(),
+                node_name: "C".to_string()
+            })
+        );
+        assert_eq!(
+            Graph::<usize>::new_from_csv(
+                Some("name,x,y\nA,0,0\nB,1,"),
+                "source,target,weight\nA,B,1",
+                &columns
+            ),
+            Err(DijkstraError::InvalidNode {
+                line: 3,
+                text: "B,1,".to_string()
+            })
+        );
+        assert!(Graph::<i64>::new_from_csv_with_signed_weights(
+            None,
+            "source,target,weight\nA,B,-1",
+            &columns
+        )
+        .is_ok());
+    }
+}
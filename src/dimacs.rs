@@ -0,0 +1,351 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::parse_input::{parse_weight_on_line, Edge, GraphNode};
+use crate::weight::Weight;
+
+/// The queries of a 9th DIMACS challenge instance: single source queries from a `.ss` file, or
+/// point to point queries from a `.p2p` file, with the nodes numbered from 0.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DimacsQueries {
+    /// the `s <node>` lines, i.e. for `SearchContext::shortest_path_tree`
+    pub sources: Vec<usize>,
+    /// the `q <start> <end>` lines, i.e. for `PathFinder::new`
+    pub routes_to_find: Vec<(usize, usize)>,
+}
+
+impl<W: Weight> Graph<W> {
+    /// read a graph in the 9th DIMACS challenge format: a `.gr` file of `p sp <nodes> <arcs>` and
+    /// `a <from> <to> <weight>` lines, and optionally a `.co` file of `v <node> <x> <y>` lines.
+    /// Arcs are one way, and the nodes are named by their DIMACS number, counting from 1.
+    /// Coordinates are usually in millionths of a degree, so they are only a valid A* estimate if
+    /// the weights are in the same units.
+    pub fn new_from_dimacs(
+        arcs: &str,
+        coordinates: Option<&str>,
+    ) -> Result<Graph<W>, DijkstraError> {
+        let mut problem: Option<(usize, usize, usize)> = None;
+        let mut edges = Vec::new();
+        let mut number_of_arcs = 0;
+        for (line, text, fields) in dimacs_lines(arcs) {
+            match (&fields[..], problem) {
+                (&["p", "sp", number_of_nodes, expected_arcs], None) => {
+                    let number_of_nodes = number_of_nodes.parse::<usize>();
+                    let expected_arcs = expected_arcs.parse::<usize>();
+                    match (number_of_nodes, expected_arcs) {
+                        (Ok(number_of_nodes), Ok(expected_arcs)) => {
+                            problem = Some((line, number_of_nodes, expected_arcs));
+                        }
+                        _ => return Err(invalid_problem_line(line, text)),
+                    }
+                }
+                (&["a", from, to, weight], Some((_, number_of_nodes, _))) => {
+                    let from_idx = dimacs_node_index(from, number_of_nodes, line, text)?;
+                    let to_idx = dimacs_node_index(to, number_of_nodes, line, text)?;
+                    let weight = parse_weight_on_line(weight, false, line, text)?;
+                    number_of_arcs += 1;
+                    // as in the text format, an edge from a node to itself is never useful
+                    if from_idx != to_idx {
+                        edges.push(Edge::new_directed(from_idx, to_idx, weight));
+                    }
+                }
+                // the problem line has to come first, and only once
+                (&["p", ..], _) | (_, None) => return Err(invalid_problem_line(line, text)),
+                _ => {
+                    return Err(DijkstraError::InvalidEdge {
+                        line,
+                        text: text.to_string(),
+                    })
+                }
+            }
+        }
+
+        let (problem_line, number_of_nodes, expected_arcs) =
+            problem.ok_or_else(|| invalid_problem_line(1, arcs.lines().next().unwrap_or("")))?;
+        if number_of_arcs != expected_arcs {
+            return Err(DijkstraError::UnexpectedEdgeCount {
+                line: problem_line,
+                expected: expected_arcs,
+                actual: number_of_arcs,
+            });
+        }
+        let mut graph_nodes: Vec<GraphNode> = (0..number_of_nodes)
+            .map(|idx| GraphNode::new(idx, (idx + 1).to_string()))
+            .collect();
+        if let Some(coordinates) = coordinates {
+            parse_dimacs_coordinates(coordinates, &mut graph_nodes)?;
+        }
+        return Graph::new(graph_nodes, edges);
+    }
+
+    /// write the graph as a DIMACS `.gr` file, with an arc for each direction of the edges that
+    /// can be travelled both ways. Nodes are numbered from 1 in index order, so the names are lost.
+    pub fn to_dimacs(&self) -> String {
+        let number_of_arcs: usize = self.connections.iter().map(|edges| edges.len()).sum();
+        let mut text = format!("p sp {} {}\n", self.number_of_nodes, number_of_arcs);
+        for edge in self.connections.iter().flatten() {
+            text.push_str(&format!(
+                "a {} {} {}\n",
+                edge.index_first + 1,
+                edge.index_second + 1,
+                edge.weight
+            ));
+        }
+        return text;
+    }
+
+    /// write the coordinates of the nodes that have them as a DIMACS `.co` file
+    pub fn coordinates_to_dimacs(&self) -> String {
+        let coordinates: Vec<(usize, (f64, f64))> = self
+            .graph_nodes
+            .iter()
+            .filter_map(|node| node.coordinates.map(|xy| (node.index, xy)))
+            .collect();
+        let mut text = format!("p aux sp co {}\n", coordinates.len());
+        for (node_idx, (x, y)) in coordinates {
+            text.push_str(&format!("v {} {} {}\n", node_idx + 1, x, y));
+        }
+        return text;
+    }
+}
+
+impl DimacsQueries {
+    /// read the `s <node>` lines of a `.ss` file and the `q <start> <end>` lines of a `.p2p` file,
+    /// for a graph with the given number of nodes. The counts on the problem line are not checked.
+    pub fn new_from_string(
+        contents: &str,
+        number_of_nodes: usize,
+    ) -> Result<DimacsQueries, DijkstraError> {
+        let mut queries = DimacsQueries::default();
+        for (line, text, fields) in dimacs_lines(contents) {
+            match fields[..] {
+                ["p", "aux", "sp", "ss" | "p2p", _] => {}
+                ["s", source] => {
+                    let source_idx = dimacs_node_index(source, number_of_nodes, line, text)?;
+                    queries.sources.push(source_idx);
+                }
+                ["q", start, end] => {
+                    let start_idx = dimacs_node_index(start, number_of_nodes, line, text)?;
+                    let end_idx = dimacs_node_index(end, number_of_nodes, line, text)?;
+                    queries.routes_to_find.push((start_idx, end_idx));
+                }
+                _ => {
+                    return Err(DijkstraError::InvalidRoute {
+                        line,
+                        text: text.to_string(),
+                    })
+                }
+            }
+        }
+        return Ok(queries);
+    }
+}
+
+/// the `v <node> <x> <y>` lines of a `.co` file. Nodes without a line keep no coordinates.
+fn parse_dimacs_coordinates(
+    contents: &str,
+    graph_nodes: &mut [GraphNode],
+) -> Result<(), DijkstraError> {
+    for (line, text, fields) in dimacs_lines(contents) {
+        match fields[..] {
+            ["p", "aux", "sp", "co", _] => {}
+            ["v", node, x, y] => {
+                let node_idx = dimacs_node_index(node, graph_nodes.len(), line, text)?;
+                match (x.parse::<f64>(), y.parse::<f64>()) {
+                    (Ok(x), Ok(y)) if x.is_finite() && y.is_finite() => {
+                        graph_nodes[node_idx].coordinates = Some((x, y));
+                    }
+                    _ => {
+                        return Err(DijkstraError::InvalidNode {
+                            line,
+                            text: text.to_string(),
+                        })
+                    }
+                }
+            }
+            _ => {
+                return Err(DijkstraError::InvalidNode {
+                    line,
+                    text: text.to_string(),
+                })
+            }
+        }
+    }
+    return Ok(());
+}
+
+/// the lines that aren't empty or `c` comments, with their line number and their fields
+fn dimacs_lines(contents: &str) -> impl Iterator<Item = (usize, &str, Vec<&str>)> {
+    return contents
+        .lines()
+        .enumerate()
+        .map(|(i, text)| (i + 1, text, text.split_whitespace().collect::<Vec<&str>>()))
+        .filter(|(_, _, fields)| !fields.is_empty() && fields[0] != "c");
+}
+
+/// DIMACS numbers the nodes from 1
+fn dimacs_node_index(
+    node: &str,
+    number_of_nodes: usize,
+    line: usize,
+    text: &str,
+) -> Result<usize, DijkstraError> {
+    match node.parse::<usize>() {
+        Ok(number) if (1..=number_of_nodes).contains(&number) => return Ok(number - 1),
+        _ => {
+            return Err(DijkstraError::UnknownNode {
+                line,
+                text: text.to_string(),
+                node_name: node.to_string(),
+            })
+        }
+    }
+}
+
+fn invalid_problem_line(line: usize, text: &str) -> DijkstraError {
+    return DijkstraError::InvalidNodeCount {
+        line,
+        text: text.to_string(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_path::PathFinder;
+    use crate::search_context::SearchContext;
+
+    use std::fs;
+
+    fn read_sample() -> Graph {
+        let arcs = fs::read_to_string("src/test/dimacs/sample.gr").unwrap();
+        let coordinates = fs::read_to_string("src/test/dimacs/sample.co").unwrap();
+        return Graph::new_from_dimacs(&arcs, Some(&coordinates)).unwrap();
+    }
+
+    #[test]
+    fn read_graph_and_coordinates() {
+        let graph = read_sample();
+        assert_eq!(graph.number_of_nodes, 5);
+        assert_eq!(graph.graph_nodes[0].node_name, "1");
+        assert_eq!(
+            graph.graph_nodes[4].coordinates,
+            Some((-73990000.0, 40750000.0))
+        );
+        assert_eq!(
+            graph.connections[0],
+            vec![Edge::new_directed(0, 1, 7), Edge::new_directed(0, 2, 3)]
+        );
+        assert!(graph.edges.iter().all(|edge| edge.is_directed));
+    }
+    #[test]
+    fn run_the_queries_through_path_finder() {
+        let graph = read_sample();
+        let p2p = fs::read_to_string("src/test/dimacs/sample.p2p").unwrap();
+        let queries = DimacsQueries::new_from_string(&p2p, graph.number_of_nodes).unwrap();
+        assert_eq!(queries.routes_to_find, vec![(0, 4), (4, 0), (1, 3)]);
+        assert!(queries.sources.is_empty());
+
+        let mut path_finder = PathFinder::new(graph, queries.routes_to_find).unwrap();
+        path_finder.dijkstra_multiple_routes();
+        let found: Vec<String> = path_finder
+            .solutions
+            .iter()
+            .map(|solution| match solution {
+                Ok(route) => route.to_string(),
+                Err(e) => e.to_string(),
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "1->3->4->5, dist 9",
+                "Are the start and end disconnected? No path found from 5 to 1",
+                "2->4, dist 1",
+            ]
+        );
+
+        let ss = fs::read_to_string("src/test/dimacs/sample.ss").unwrap();
+        let queries =
+            DimacsQueries::new_from_string(&ss, path_finder.graph.number_of_nodes).unwrap();
+        assert_eq!(queries.sources, vec![0, 1]);
+        let mut context = SearchContext::new(path_finder.graph.number_of_nodes);
+        let tree = context.shortest_path_tree(&path_finder.graph, queries.sources[0]);
+        assert_eq!(
+            tree.distances,
+            vec![Some(0), Some(7), Some(3), Some(5), Some(9)]
+        );
+    }
+    #[test]
+    fn write_and_read_back() {
+        let graph = read_sample();
+        let written =
+            Graph::new_from_dimacs(&graph.to_dimacs(), Some(&graph.coordinates_to_dimacs()))
+                .unwrap();
+        assert_eq!(written.connections, graph.connections);
+        assert_eq!(written.graph_nodes, graph.graph_nodes);
+
+        // bidirectional edges become an arc each way
+        let contents = fs::read_to_string("src/test/uk.txt").unwrap();
+        let uk = Graph::new_from_string(&contents).unwrap();
+        let written = Graph::<usize>::new_from_dimacs(&uk.to_dimacs(), None).unwrap();
+        let mut context = SearchContext::new(uk.number_of_nodes);
+        for start_idx in 0..uk.number_of_nodes {
+            for end_idx in 0..uk.number_of_nodes {
+                assert_eq!(
+                    context.dijkstra(&written, start_idx, end_idx),
+                    context.dijkstra(&uk, start_idx, end_idx)
+                );
+            }
+        }
+    }
+    #[test]
+    fn errors_report_the_line() {
+        let read = |arcs: &str| Graph::<usize>::new_from_dimacs(arcs, None);
+        assert_eq!(
+            read("c no problem line\na 1 2 3"),
+            Err(DijkstraError::InvalidNodeCount {
+                line: 2,
+                text: "a 1 2 3".to_string()
+            })
+        );
+        assert_eq!(
+            read("p sp 2 1\na 1 3 4"),
+            Err(DijkstraError::UnknownNode {
+                line: 2,
+                text: "a 1 3 4".to_string(),
+                node_name: "3".to_string()
+            })
+        );
+        assert_eq!(
+            read("p sp 2 1\nc\na 1 2 -4"),
+            Err(DijkstraError::NegativeWeight {
+                line: 3,
+                text: "a 1 2 -4".to_string(),
+                weight: "-4".to_string()
+            })
+        );
+        assert_eq!(
+            read("p sp 2 2\na 1 2 4"),
+            Err(DijkstraError::UnexpectedEdgeCount {
+                line: 1,
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(
+            read("p sp 2 1\ne 1 2 4"),
+            Err(DijkstraError::InvalidEdge {
+                line: 2,
+                text: "e 1 2 4".to_string()
+            })
+        );
+        assert_eq!(
+            DimacsQueries::new_from_string("q 1 0", 2),
+            Err(DijkstraError::UnknownNode {
+                line: 1,
+                text: "q 1 0".to_string(),
+                node_name: "0".to_string()
+            })
+        );
+    }
+}
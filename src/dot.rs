@@ -0,0 +1,630 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::find_path::PathFinder;
+use crate::parse_input::{get_node_index_from_node_name, parse_weight_on_line, Edge, GraphNode};
+use crate::route::Route;
+use crate::weight::Weight;
+
+use std::collections::BTreeMap;
+
+/// the colours given to the routes in turn, starting again once they run out
+const ROUTE_COLOURS: [&str; 8] = [
+    "red", "blue", "green3", "orange", "purple", "cyan3", "magenta", "brown",
+];
+
+impl<W: Weight> Graph<W> {
+    /// write the graph in Graphviz's DOT language, i.e. for `$ dot -Tsvg`, with each edge
+    /// labelled with its weight. It is a `digraph` if any edge is directed, with the
+    /// bidirectional edges drawn without arrows.
+    pub fn to_dot(&self) -> String {
+        return self.to_dot_with_routes(&[]);
+    }
+
+    /// as to_dot, with the nodes and edges of each route highlighted in its own colour. Nodes
+    /// on more than one route are split between their colours, and edges are drawn once for
+    /// each route that uses them.
+    pub fn to_dot_with_routes(&self, routes: &[Route<W>]) -> String {
+        let is_directed = self.edges.iter().any(|edge| edge.is_directed);
+        let mut node_colours: Vec<Vec<&str>> = vec![Vec::new(); self.number_of_nodes];
+        let mut edge_colours: Vec<Vec<&str>> = vec![Vec::new(); self.edges.len()];
+        for (route_number, route) in routes.iter().enumerate() {
+            let colour = ROUTE_COLOURS[route_number % ROUTE_COLOURS.len()];
+            for &node_idx in &route.nodes {
+                if !node_colours[node_idx].contains(&colour) {
+                    node_colours[node_idx].push(colour);
+                }
+            }
+            for (leg, &weight) in route.nodes.windows(2).zip(&route.leg_weights) {
+                // the route follows the first edge with the weight of the leg
+                let edge_pos = self.edges.iter().position(|edge| {
+                    let is_forwards = edge.index_first == leg[0] && edge.index_second == leg[1];
+                    let is_backwards = edge.index_first == leg[1] && edge.index_second == leg[0];
+                    return edge.weight == weight
+                        && (is_forwards || (is_backwards && !edge.is_directed));
+                });
+                if let Some(edge_pos) = edge_pos {
+                    edge_colours[edge_pos].push(colour);
+                }
+            }
+        }
+
+        let mut dot = String::from(if is_directed {
+            "digraph {\n"
+        } else {
+            "graph {\n"
+        });
+        for (route_number, route) in routes.iter().enumerate() {
+            let colour = ROUTE_COLOURS[route_number % ROUTE_COLOURS.len()];
+            dot.push_str(&format!(
+                "  // {}. {}: {}\n",
+                route_number + 1,
+                colour,
+                route
+            ));
+        }
+        for node in &self.graph_nodes {
+            let mut attributes: Vec<(&str, String)> = node
+                .attributes
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.to_string()))
+                .collect();
+            if let Some((x, y)) = node.coordinates {
+                attributes.push(("pos", format!("{},{}!", x, y)));
+            }
+            match &node_colours[node.index][..] {
+                [] => {}
+                [colour] => {
+                    attributes.push(("style", "filled".to_string()));
+                    attributes.push(("fillcolor", colour.to_string()));
+                }
+                colours => {
+                    attributes.push(("style", "wedged".to_string()));
+                    attributes.push(("fillcolor", colours.join(":")));
+                }
+            }
+            dot.push_str(&format!(
+                "  {}{};\n",
+                dot_id(&node.node_name),
+                dot_attributes(&attributes)
+            ));
+        }
+        let edge_op = if is_directed { "->" } else { "--" };
+        for (edge, colours) in self.edges.iter().zip(&edge_colours) {
+            let mut attributes = vec![("label", edge.weight.to_string())];
+            if is_directed && !edge.is_directed {
+                attributes.push(("dir", "none".to_string()));
+            }
+            if !colours.is_empty() {
+                attributes.push(("color", colours.join(":")));
+                attributes.push(("penwidth", "2".to_string()));
+            }
+            dot.push_str(&format!(
+                "  {} {} {}{};\n",
+                dot_id(&self.graph_nodes[edge.index_first].node_name),
+                edge_op,
+                dot_id(&self.graph_nodes[edge.index_second].node_name),
+                dot_attributes(&attributes)
+            ));
+        }
+        dot.push_str("}\n");
+        return dot;
+    }
+
+    /// read a simple `graph` or `digraph` in Graphviz's DOT language: node statements, edge
+    /// statements, i.e. `a -> b -> c [weight=2]`, and attribute statements, which are ignored.
+    /// Subgraphs, ports and HTML labels aren't supported. The weight of each edge is its
+    /// `weight` attribute, or its `label` if it has no weight. In a `digraph` an edge with
+    /// `dir=none` or `dir=both` can be travelled both ways. Nodes are named by their ID, in the
+    /// order they first appear, a node's `pos` gives its coordinates, and its other attributes
+    /// are kept in `attributes`. The weights can't be negative.
+    pub fn new_from_dot(contents: &str) -> Result<Graph<W>, DijkstraError> {
+        return DotParser::new(contents)?.parse_graph(false);
+    }
+
+    /// as new_from_dot, but negative weights are allowed, for use with `bellman_ford`
+    pub fn new_from_dot_with_signed_weights(contents: &str) -> Result<Graph<W>, DijkstraError> {
+        return DotParser::new(contents)?.parse_graph(true);
+    }
+}
+
+impl<W: Weight> PathFinder<W> {
+    /// the graph in DOT, with the routes found so far highlighted, see `Graph::to_dot_with_routes`
+    pub fn to_dot(&self) -> String {
+        let routes: Vec<Route<W>> = self
+            .solutions
+            .iter()
+            .filter_map(|solution| solution.as_ref().ok())
+            .cloned()
+            .collect();
+        return self.graph.to_dot_with_routes(&routes);
+    }
+}
+
+/// every ID is quoted, so that any node name can be written
+fn dot_id(id: &str) -> String {
+    return format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+fn dot_attributes(attributes: &[(&str, String)]) -> String {
+    if attributes.is_empty() {
+        return String::new();
+    }
+    let attributes: Vec<String> = attributes
+        .iter()
+        .map(|(key, value)| format!("{}={}", dot_id(key), dot_id(value)))
+        .collect();
+    return format!(" [{}]", attributes.join(", "));
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum DotToken {
+    /// a bare or quoted ID, or a keyword such as `digraph`
+    Id(String),
+    /// `{`, `}`, `[`, `]`, `=`, `;`, `,`, `--` or `->`
+    Symbol(&'static str),
+}
+
+/// reads the statements of a DOT file, one token at a time
+struct DotParser<'a> {
+    lines: Vec<&'a str>,
+    /// each token with the line it starts on
+    tokens: Vec<(DotToken, usize)>,
+    next_token: usize,
+}
+
+impl<'a> DotParser<'a> {
+    fn new(contents: &'a str) -> Result<DotParser<'a>, DijkstraError> {
+        let lines: Vec<&str> = contents.lines().collect();
+        let tokens = tokenize_dot(contents, &lines)?;
+        return Ok(DotParser {
+            lines,
+            tokens,
+            next_token: 0,
+        });
+    }
+
+    fn parse_graph<W: Weight>(
+        mut self,
+        allow_negative_weights: bool,
+    ) -> Result<Graph<W>, DijkstraError> {
+        if self.peek() == Some(&DotToken::Id("strict".to_string())) {
+            self.next_token += 1;
+        }
+        let is_directed = match self.next() {
+            Some(DotToken::Id(keyword)) if keyword == "graph" => false,
+            Some(DotToken::Id(keyword)) if keyword == "digraph" => true,
+            _ => return Err(self.invalid_dot()),
+        };
+        if let Some(DotToken::Id(_)) = self.peek() {
+            self.next_token += 1;
+        }
+        self.expect(DotToken::Symbol("{"))?;
+
+        let mut graph_nodes: Vec<GraphNode> = Vec::new();
+        let mut edges = Vec::new();
+        loop {
+            let line = self.line();
+            let id = match self.next() {
+                Some(DotToken::Symbol("}")) => break,
+                Some(DotToken::Symbol(";")) => continue,
+                Some(DotToken::Id(id)) => id,
+                _ => return Err(self.invalid_dot()),
+            };
+            match self.peek() {
+                // default attributes for the graph, nodes or edges
+                _ if ["graph", "node", "edge"].contains(&id.as_str()) => {
+                    self.parse_attributes()?;
+                }
+                // an attribute of the graph
+                Some(DotToken::Symbol("=")) => {
+                    self.next_token += 1;
+                    self.expect_id()?;
+                }
+                Some(DotToken::Symbol("--" | "->")) => {
+                    let mut node_names = vec![id];
+                    while let Some(DotToken::Symbol(edge_op)) = self.peek() {
+                        if !matches!(*edge_op, "--" | "->") {
+                            break;
+                        }
+                        if (*edge_op == "->") != is_directed {
+                            return Err(self.invalid_dot());
+                        }
+                        self.next_token += 1;
+                        node_names.push(self.expect_id()?);
+                    }
+                    let attributes = self.parse_attributes()?;
+                    let text = self.lines[line - 1];
+                    let weight = match attributes.get("weight").or(attributes.get("label")) {
+                        Some(weight) => {
+                            parse_weight_on_line(weight, allow_negative_weights, line, text)?
+                        }
+                        None => {
+                            return Err(DijkstraError::InvalidEdge {
+                                line,
+                                text: text.to_string(),
+                            })
+                        }
+                    };
+                    let is_edge_directed = is_directed
+                        && !matches!(
+                            attributes.get("dir").map(|dir| dir.as_str()),
+                            Some("none" | "both")
+                        );
+                    let node_indexes: Vec<usize> = node_names
+                        .iter()
+                        .map(|node_name| add_dot_node(&mut graph_nodes, node_name))
+                        .collect();
+                    for pair in node_indexes.windows(2) {
+                        // as in the text format, an edge from a node to itself is never useful
+                        if pair[0] == pair[1] {
+                            continue;
+                        }
+                        if is_edge_directed {
+                            edges.push(Edge::new_directed(pair[0], pair[1], weight));
+                        } else {
+                            edges.push(Edge::new(pair[0], pair[1], weight));
+                        }
+                    }
+                }
+                _ => {
+                    let attributes = self.parse_attributes()?;
+                    let node_idx = add_dot_node(&mut graph_nodes, &id);
+                    let node = &mut graph_nodes[node_idx];
+                    for (key, value) in attributes {
+                        if key != "pos" {
+                            node.attributes.insert(key, value);
+                            continue;
+                        }
+                        let coordinates: Vec<Result<f64, _>> = value
+                            .trim_end_matches('!')
+                            .split(',')
+                            .map(|coordinate| coordinate.trim().parse::<f64>())
+                            .collect();
+                        match coordinates[..] {
+                            [Ok(x), Ok(y)] if x.is_finite() && y.is_finite() => {
+                                node.coordinates = Some((x, y));
+                            }
+                            _ => {
+                                return Err(DijkstraError::InvalidNode {
+                                    line,
+                                    text: self.lines[line - 1].to_string(),
+                                })
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if self.peek().is_some() {
+            return Err(self.invalid_dot());
+        }
+        return Graph::new_allowing_negative_weights(graph_nodes, edges, allow_negative_weights);
+    }
+
+    /// any number of `[key=value, ...]` lists, with the later value of a repeated key kept
+    fn parse_attributes(&mut self) -> Result<BTreeMap<String, String>, DijkstraError> {
+        let mut attributes = BTreeMap::new();
+        while self.peek() == Some(&DotToken::Symbol("[")) {
+            self.next_token += 1;
+            loop {
+                match self.next() {
+                    Some(DotToken::Symbol("]")) => break,
+                    Some(DotToken::Symbol(",") | DotToken::Symbol(";")) => continue,
+                    Some(DotToken::Id(key)) => {
+                        self.expect(DotToken::Symbol("="))?;
+                        let value = self.expect_id()?;
+                        attributes.insert(key, value);
+                    }
+                    _ => return Err(self.invalid_dot()),
+                }
+            }
+        }
+        return Ok(attributes);
+    }
+
+    fn peek(&self) -> Option<&DotToken> {
+        return self.tokens.get(self.next_token).map(|(token, _)| token);
+    }
+
+    fn next(&mut self) -> Option<DotToken> {
+        let token = self.peek().cloned();
+        self.next_token += 1;
+        return token;
+    }
+
+    /// the line of the next token, or the last line at the end of the file
+    fn line(&self) -> usize {
+        return match self.tokens.get(self.next_token) {
+            Some(&(_, line)) => line,
+            None => self.lines.len().max(1),
+        };
+    }
+
+    fn expect(&mut self, expected: DotToken) -> Result<(), DijkstraError> {
+        if self.peek() != Some(&expected) {
+            return Err(self.invalid_dot());
+        }
+        self.next_token += 1;
+        return Ok(());
+    }
+
+    fn expect_id(&mut self) -> Result<String, DijkstraError> {
+        match self.peek() {
+            Some(DotToken::Id(id)) => {
+                let id = id.to_string();
+                self.next_token += 1;
+                return Ok(id);
+            }
+            _ => return Err(self.invalid_dot()),
+        }
+    }
+
+    /// an error for the line of the token that couldn't be read, which is the last token read
+    fn invalid_dot(&self) -> DijkstraError {
+        let token_idx = self.next_token.min(self.tokens.len()).saturating_sub(1);
+        let line = self.tokens.get(token_idx).map_or(1, |&(_, line)| line);
+        return invalid_dot_line(line, &self.lines);
+    }
+}
+
+fn invalid_dot_line(line: usize, lines: &[&str]) -> DijkstraError {
+    return DijkstraError::InvalidDot {
+        line,
+        text: lines.get(line - 1).unwrap_or(&"").to_string(),
+    };
+}
+
+/// the index of the node with the name, adding it to the end of the node list if it is new
+fn add_dot_node(graph_nodes: &mut Vec<GraphNode>, node_name: &str) -> usize {
+    if let Some(node_idx) = get_node_index_from_node_name(node_name, graph_nodes) {
+        return node_idx;
+    }
+    graph_nodes.push(GraphNode::new(graph_nodes.len(), node_name.to_string()));
+    return graph_nodes.len() - 1;
+}
+
+/// split the DOT file into IDs and symbols, skipping comments
+fn tokenize_dot(contents: &str, lines: &[&str]) -> Result<Vec<(DotToken, usize)>, DijkstraError> {
+    let chars: Vec<char> = contents.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut pos = 0;
+    let is_bare_id_char = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    while pos < chars.len() {
+        let c = chars[pos];
+        let next = chars.get(pos + 1).copied();
+        let token_line = line;
+        match c {
+            '\n' => {
+                line += 1;
+                pos += 1;
+            }
+            _ if c.is_whitespace() => pos += 1,
+            // a line starting with `#` is output from the C preprocessor, so it is skipped
+            '#' if chars[..pos]
+                .iter()
+                .rev()
+                .take_while(|&&c| c != '\n')
+                .all(|c| c.is_whitespace()) =>
+            {
+                while pos < chars.len() && chars[pos] != '\n' {
+                    pos += 1;
+                }
+            }
+            '/' if next == Some('/') => {
+                while pos < chars.len() && chars[pos] != '\n' {
+                    pos += 1;
+                }
+            }
+            '/' if next == Some('*') => {
+                pos += 2;
+                while pos < chars.len() && !(chars[pos] == '*' && chars.get(pos + 1) == Some(&'/'))
+                {
+                    if chars[pos] == '\n' {
+                        line += 1;
+                    }
+                    pos += 1;
+                }
+                if pos >= chars.len() {
+                    return Err(invalid_dot_line(token_line, lines));
+                }
+                pos += 2;
+            }
+            '-' if next == Some('-') || next == Some('>') => {
+                tokens.push((
+                    DotToken::Symbol(if next == Some('-') { "--" } else { "->" }),
+                    line,
+                ));
+                pos += 2;
+            }
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' => {
+                let symbol = match c {
+                    '{' => "{",
+                    '}' => "}",
+                    '[' => "[",
+                    ']' => "]",
+                    '=' => "=",
+                    ';' => ";",
+                    _ => ",",
+                };
+                tokens.push((DotToken::Symbol(symbol), line));
+                pos += 1;
+            }
+            '"' => {
+                let mut id = String::new();
+                pos += 1;
+                loop {
+                    match (chars.get(pos), chars.get(pos + 1)) {
+                        (None, _) => return Err(invalid_dot_line(token_line, lines)),
+                        (Some('"'), _) => break,
+                        (Some('\\'), Some(&escaped @ ('"' | '\\'))) => {
+                            id.push(escaped);
+                            pos += 2;
+                        }
+                        // a backslash at the end of a line continues the string on the next line
+                        (Some('\\'), Some('\n')) => {
+                            line += 1;
+                            pos += 2;
+                        }
+                        (Some(&c), _) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            id.push(c);
+                            pos += 1;
+                        }
+                    }
+                }
+                tokens.push((DotToken::Id(id), token_line));
+                pos += 1;
+            }
+            _ if is_bare_id_char(c) || c == '-' => {
+                let start = pos;
+                pos += 1;
+                while pos < chars.len() && is_bare_id_char(chars[pos]) {
+                    pos += 1;
+                }
+                tokens.push((DotToken::Id(chars[start..pos].iter().collect()), line));
+            }
+            // ports, HTML labels and anything else
+            _ => return Err(invalid_dot_line(token_line, lines)),
+        }
+    }
+    return Ok(tokens);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weight::TotalF64;
+
+    use std::fs;
+
+    #[test]
+    fn write_graph_with_routes() {
+        let mut path_finder = PathFinder::new_from_string(
+            "4\nA\nB 0 1\nC\nD\n\n4\nA B 1\nB -> C 2\nA C 5\nC D 1\n\nA C\nB D",
+        )
+        .unwrap();
+        path_finder.dijkstra_multiple_routes();
+        assert_eq!(
+            path_finder.to_dot(),
+            [
+                "digraph {",
+                "  // 1. red: A->B->C, dist 3",
+                "  // 2. blue: B->C->D, dist 3",
+                "  \"A\" [\"style\"=\"filled\", \"fillcolor\"=\"red\"];",
+                "  \"B\" [\"pos\"=\"0,1!\", \"style\"=\"wedged\", \"fillcolor\"=\"red:blue\"];",
+                "  \"C\" [\"style\"=\"wedged\", \"fillcolor\"=\"red:blue\"];",
+                "  \"D\" [\"style\"=\"filled\", \"fillcolor\"=\"blue\"];",
+                "  \"A\" -> \"B\" [\"label\"=\"1\", \"dir\"=\"none\", \"color\"=\"red\", \"penwidth\"=\"2\"];",
+                "  \"B\" -> \"C\" [\"label\"=\"2\", \"color\"=\"red:blue\", \"penwidth\"=\"2\"];",
+                "  \"A\" -> \"C\" [\"label\"=\"5\", \"dir\"=\"none\"];",
+                "  \"C\" -> \"D\" [\"label\"=\"1\", \"dir\"=\"none\", \"color\"=\"blue\", \"penwidth\"=\"2\"];",
+                "}\n",
+            ]
+            .join("\n")
+        );
+    }
+    #[test]
+    fn write_and_read_back() {
+        let contents = fs::read_to_string("src/test/uk.txt").unwrap();
+        let graph = Graph::new_from_string(&contents).unwrap();
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("graph {\n  \"Inverness\";\n"));
+        assert!(dot.contains("\n  \"Inverness\" -- \"Glasgow\" [\"label\"=\"167\"];\n"));
+        assert_eq!(Graph::new_from_dot(&dot), Ok(graph));
+
+        // names that have to be escaped, which can't be given in the text format
+        let mut graph_nodes = vec![
+            GraphNode::new_with_coordinates(0, "say \"hi\"".to_string(), 1.5, -2.0),
+            GraphNode::new(1, "back\\slash".to_string()),
+            GraphNode::new(2, "C".to_string()),
+        ];
+        graph_nodes[2]
+            .attributes
+            .insert("shape".to_string(), "box".to_string());
+        let half = TotalF64::new(0.5).unwrap();
+        let graph = Graph::new(
+            graph_nodes,
+            vec![Edge::new_directed(0, 1, half), Edge::new(2, 1, half)],
+        )
+        .unwrap();
+        assert_eq!(Graph::new_from_dot(&graph.to_dot()), Ok(graph));
+    }
+    #[test]
+    fn read_simple_dot() {
+        let dot = "/* a road map */\nstrict digraph roads {\n  rankdir=LR; node [shape=circle]\n  a [label=\"Start\", pos=\"1,2\"]\n  a -> b -> c [weight=2] // a chain\n  c -> a [label=5, dir=none];\n  # preprocessor line\n  d\n}\n";
+        let graph = Graph::<usize>::new_from_dot(dot).unwrap();
+        let names: Vec<&str> = graph
+            .graph_nodes
+            .iter()
+            .map(|node| node.node_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "b", "c", "d"]);
+        assert_eq!(graph.graph_nodes[0].coordinates, Some((1.0, 2.0)));
+        assert_eq!(
+            graph.graph_nodes[0]
+                .attributes
+                .get("label")
+                .map(|l| l.as_str()),
+            Some("Start")
+        );
+        assert_eq!(
+            graph.edges,
+            vec![
+                Edge::new_directed(0, 1, 2),
+                Edge::new_directed(1, 2, 2),
+                Edge::new(2, 0, 5)
+            ]
+        );
+    }
+    #[test]
+    fn invalid_dot_is_an_error() {
+        let read = |dot: &str| Graph::<usize>::new_from_dot(dot);
+        assert_eq!(
+            read("graph {\n  a -- b [label=1]\n  b -> c [label=1]\n}"),
+            Err(DijkstraError::InvalidDot {
+                line: 3,
+                text: "  b -> c [label=1]".to_string()
+            })
+        );
+        assert_eq!(
+            read("graph {\n  a -- b\n}"),
+            Err(DijkstraError::InvalidEdge {
+                line: 2,
+                text: "  a -- b".to_string()
+            })
+        );
+        assert_eq!(
+            read("graph {\n  a -- b [weight=-1]\n}"),
+            Err(DijkstraError::NegativeWeight {
+                line: 2,
+                text: "  a -- b [weight=-1]".to_string(),
+                weight: "-1".to_string()
+            })
+        );
+        assert!(
+            Graph::<i64>::new_from_dot_with_signed_weights("graph {\n  a -- b [weight=-1]\n}")
+                .is_ok()
+        );
+        assert_eq!(
+            read("graph {\n  subgraph { a }\n}"),
+            Err(DijkstraError::InvalidDot {
+                line: 2,
+                text: "  subgraph { a }".to_string()
+            })
+        );
+        assert_eq!(
+            read("graph {\n  a:n -- b [weight=1]\n}"),
+            Err(DijkstraError::InvalidDot {
+                line: 2,
+                text: "  a:n -- b [weight=1]".to_string()
+            })
+        );
+        assert!(matches!(
+            read("graph {\n  a"),
+            Err(DijkstraError::InvalidDot { .. })
+        ));
+    }
+}
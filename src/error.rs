@@ -0,0 +1,400 @@
+use crate::construct_graph::Graph;
+use crate::weight::WeightParseError;
+
+use std::fmt;
+
+/// Everything that can go wrong while reading the input or finding a route.
+/// Errors found while parsing carry the line number (counting from 1) and the text of the
+/// offending line. Parsing a single section numbers lines from the start of that section,
+/// parsing a whole file numbers them from the start of the file.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DijkstraError {
+    /// the input is not made of node, edge and route sections separated by empty lines
+    InvalidFileFormat {
+        sections_found: usize,
+    },
+    /// the first line of the node section is not an integer
+    InvalidNodeCount {
+        line: usize,
+        text: String,
+    },
+    UnexpectedNodeCount {
+        line: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// the first line of the edge section is not i.e. `4` or `4 directed`
+    InvalidEdgeCount {
+        line: usize,
+        text: String,
+    },
+    UnexpectedEdgeCount {
+        line: usize,
+        expected: usize,
+        actual: usize,
+    },
+    InvalidNode {
+        line: usize,
+        text: String,
+    },
+    InvalidEdge {
+        line: usize,
+        text: String,
+    },
+    NonIntegerWeight {
+        line: usize,
+        text: String,
+        weight: String,
+    },
+    /// a weight that can't be read as the weight type of the graph
+    InvalidWeight {
+        line: usize,
+        text: String,
+        weight: String,
+        reason: WeightParseError,
+    },
+    NegativeWeight {
+        line: usize,
+        text: String,
+        weight: String,
+    },
+    /// an edge or route refers to a node that is not in the node list
+    UnknownNode {
+        line: usize,
+        text: String,
+        node_name: String,
+    },
+    /// a node name given in code, i.e. to a `GraphBuilder`, that is not in the node list
+    UnknownNodeName {
+        node_name: String,
+    },
+    /// a negative weight given in code, i.e. to a `GraphBuilder` that isn't building a graph
+    /// with signed weights
+    NegativeEdgeWeight {
+        from_name: String,
+        to_name: String,
+        weight: String,
+    },
+    InvalidRoute {
+        line: usize,
+        text: String,
+    },
+    /// the route starts and ends at the same node. For JSON input, `line` is the position of the
+    /// route in `routes`, counting from 1.
+    SelfReferentialRoute {
+        line: usize,
+        text: String,
+        node_name: String,
+    },
+    /// there is no path between the start and the end of the route
+    Disconnected {
+        start_idx: usize,
+        end_idx: usize,
+        start_name: String,
+        end_name: String,
+    },
+    /// no path to the end was found, but some paths were dropped because their distance was
+    /// too large for the weight type, so the end may only be reachable through them
+    DistanceOverflow {
+        start_idx: usize,
+        end_idx: usize,
+        start_name: String,
+        end_name: String,
+    },
+    /// a cycle of edges whose weights add up to less than zero can be reached from the start of
+    /// the route, so there is no shortest route. The cycle is given as the names of its nodes in
+    /// the order they are travelled, without repeating the first node at the end.
+    NegativeCycle {
+        cycle: Vec<String>,
+    },
+    /// a node index that does not belong to the graph
+    InvalidNodeIndex {
+        node_idx: usize,
+    },
+    /// consecutive nodes of a route that are not connected by an edge
+    MissingEdge {
+        from_idx: usize,
+        to_idx: usize,
+    },
+    /// the weights of a spanning tree add up to more than the weight type can hold
+    TotalWeightOverflow,
+    /// the JSON input doesn't match the schema, or refers to a node that isn't in the node list
+    InvalidJson {
+        message: String,
+    },
+    /// a CSV file can't be read, i.e. a row has the wrong number of fields or a column is missing
+    /// from the header row. Rows count from 1, with the header as row 1.
+    InvalidCsv {
+        line: usize,
+        message: String,
+    },
+    /// a line of a DOT file that isn't part of a single graph of nodes, edges and attributes,
+    /// i.e. a subgraph or a port
+    InvalidDot {
+        line: usize,
+        text: String,
+    },
+    /// a GraphML file that isn't well formed XML, or has elements that aren't supported, i.e.
+    /// hyperedges, ports or nested graphs
+    InvalidGraphMl {
+        line: usize,
+        message: String,
+    },
+}
+
+impl DijkstraError {
+    /// there is no path between the nodes of the graph
+    pub(crate) fn disconnected<W>(
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+    ) -> DijkstraError {
+        return DijkstraError::Disconnected {
+            start_idx,
+            end_idx,
+            start_name: graph.graph_nodes[start_idx].node_name.to_string(),
+            end_name: graph.graph_nodes[end_idx].node_name.to_string(),
+        };
+    }
+
+    /// the end may only be reachable along a path that is too long for the weight type
+    pub(crate) fn distance_overflow<W>(
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+    ) -> DijkstraError {
+        return DijkstraError::DistanceOverflow {
+            start_idx,
+            end_idx,
+            start_name: graph.graph_nodes[start_idx].node_name.to_string(),
+            end_name: graph.graph_nodes[end_idx].node_name.to_string(),
+        };
+    }
+
+    /// move the line number on by the lines before the section that was parsed
+    pub(crate) fn offset_line(mut self, lines_before: usize) -> DijkstraError {
+        match &mut self {
+            DijkstraError::InvalidNodeCount { line, .. }
+            | DijkstraError::UnexpectedNodeCount { line, .. }
+            | DijkstraError::InvalidEdgeCount { line, .. }
+            | DijkstraError::UnexpectedEdgeCount { line, .. }
+            | DijkstraError::InvalidNode { line, .. }
+            | DijkstraError::InvalidEdge { line, .. }
+            | DijkstraError::NonIntegerWeight { line, .. }
+            | DijkstraError::InvalidWeight { line, .. }
+            | DijkstraError::NegativeWeight { line, .. }
+            | DijkstraError::UnknownNode { line, .. }
+            | DijkstraError::InvalidRoute { line, .. }
+            | DijkstraError::SelfReferentialRoute { line, .. }
+            | DijkstraError::InvalidCsv { line, .. }
+            | DijkstraError::InvalidDot { line, .. }
+            | DijkstraError::InvalidGraphMl { line, .. } => *line += lines_before,
+            DijkstraError::InvalidFileFormat { .. }
+            | DijkstraError::UnknownNodeName { .. }
+            | DijkstraError::NegativeEdgeWeight { .. }
+            | DijkstraError::Disconnected { .. }
+            | DijkstraError::DistanceOverflow { .. }
+            | DijkstraError::NegativeCycle { .. }
+            | DijkstraError::InvalidNodeIndex { .. }
+            | DijkstraError::MissingEdge { .. }
+            | DijkstraError::TotalWeightOverflow
+            | DijkstraError::InvalidJson { .. } => {}
+        }
+        return self;
+    }
+}
+
+impl fmt::Display for DijkstraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DijkstraError::InvalidFileFormat { sections_found } => write!(
+                f,
+                "Invalid file format. Expected nodes, edges and routes separated by empty lines, {} sections found.",
+                sections_found
+            ),
+            DijkstraError::InvalidNodeCount { line, text } => write!(
+                f,
+                "Line {}: expected an integer number of nodes, {:?} found.",
+                line, text
+            ),
+            DijkstraError::UnexpectedNodeCount {
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Line {}: unexpected number of nodes. Expected: {}, actual: {}",
+                line, expected, actual
+            ),
+            DijkstraError::InvalidEdgeCount { line, text } => write!(
+                f,
+                "Line {}: number of edges {:?} is invalid. Expected i.e. `4` or `4 directed`.",
+                line, text
+            ),
+            DijkstraError::UnexpectedEdgeCount {
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Line {}: unexpected number of edges. Expected: {}, actual: {}",
+                line, expected, actual
+            ),
+            DijkstraError::InvalidNode { line, text } => write!(
+                f,
+                "Line {}: node {:?} is invalid. Expected a name, optionally followed by x and y coordinates.",
+                line, text
+            ),
+            DijkstraError::InvalidEdge { line, text } => write!(
+                f,
+                "Line {}: edge {:?} is invalid. Please check the input.",
+                line, text
+            ),
+            DijkstraError::NonIntegerWeight { line, text, weight } => write!(
+                f,
+                "Line {}: distance between edges should be an integer, {} found in {:?}.",
+                line, weight, text
+            ),
+            DijkstraError::InvalidWeight {
+                line,
+                text,
+                weight,
+                reason,
+            } => write!(
+                f,
+                "Line {}: distance between edges {}, {} found in {:?}.",
+                line, reason, weight, text
+            ),
+            DijkstraError::NegativeWeight { line, text, weight } => write!(
+                f,
+                "Line {}: distance between edges can't be negative, {} found in {:?}.",
+                line, weight, text
+            ),
+            DijkstraError::UnknownNode {
+                line,
+                text,
+                node_name,
+            } => write!(
+                f,
+                "Line {}: node {} in {:?} should be present in node list.",
+                line, node_name, text
+            ),
+            DijkstraError::UnknownNodeName { node_name } => write!(
+                f,
+                "Node {} should be present in node list.",
+                node_name
+            ),
+            DijkstraError::NegativeEdgeWeight {
+                from_name,
+                to_name,
+                weight,
+            } => write!(
+                f,
+                "Distance between edges can't be negative, {} found from {} to {}.",
+                weight, from_name, to_name
+            ),
+            DijkstraError::InvalidRoute { line, text } => write!(
+                f,
+                "Line {}: route {:?} is invalid. Please check the input.",
+                line, text
+            ),
+            DijkstraError::SelfReferentialRoute {
+                line, node_name, ..
+            } => write!(
+                f,
+                "Line {}: route is self referential. Dist from {} to {} = 0",
+                line, node_name, node_name
+            ),
+            DijkstraError::Disconnected {
+                start_name,
+                end_name,
+                ..
+            } => write!(
+                f,
+                "Are the start and end disconnected? No path found from {} to {}",
+                start_name, end_name
+            ),
+            DijkstraError::DistanceOverflow {
+                start_name,
+                end_name,
+                ..
+            } => write!(
+                f,
+                "The distance from {} to {} is too large for the weight type.",
+                start_name, end_name
+            ),
+            DijkstraError::NegativeCycle { cycle } => match cycle.first() {
+                Some(first_name) => write!(
+                    f,
+                    "There is no shortest route, as it can go round the negative cycle {}->{} any number of times.",
+                    cycle.join("->"),
+                    first_name
+                ),
+                None => write!(
+                    f,
+                    "There is no shortest route, as it can go round a negative cycle any number of times."
+                ),
+            },
+            DijkstraError::InvalidNodeIndex { node_idx } => write!(
+                f,
+                "Error in the indexing for the route travelled. Node {} not found.",
+                node_idx
+            ),
+            DijkstraError::MissingEdge { from_idx, to_idx } => write!(
+                f,
+                "Error in the route travelled. There is no edge from node {} to node {}.",
+                from_idx, to_idx
+            ),
+            DijkstraError::TotalWeightOverflow => write!(
+                f,
+                "The total weight of the spanning tree is too large for the weight type."
+            ),
+            DijkstraError::InvalidJson { message } => write!(f, "Invalid JSON input: {}", message),
+            DijkstraError::InvalidCsv { line, message } => {
+                write!(f, "Line {}: invalid CSV, {}.", line, message)
+            }
+            DijkstraError::InvalidDot { line, text } => write!(
+                f,
+                "Line {}: {:?} can't be read as DOT. Only the nodes, edges and attributes of a single graph are supported.",
+                line, text
+            ),
+            DijkstraError::InvalidGraphMl { line, message } => {
+                write!(f, "Line {}: invalid GraphML, {}.", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DijkstraError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_names_the_nodes() {
+        let disconnected = DijkstraError::Disconnected {
+            start_idx: 4,
+            end_idx: 0,
+            start_name: "5".to_string(),
+            end_name: "1".to_string(),
+        };
+        assert_eq!(
+            disconnected.to_string(),
+            "Are the start and end disconnected? No path found from 5 to 1"
+        );
+        let cycle = DijkstraError::NegativeCycle {
+            cycle: vec!["A".to_string(), "B".to_string()],
+        };
+        assert_eq!(
+            cycle.to_string(),
+            "There is no shortest route, as it can go round the negative cycle A->B->A any number of times."
+        );
+        let empty_cycle = DijkstraError::NegativeCycle { cycle: Vec::new() };
+        assert_eq!(
+            empty_cycle.to_string(),
+            "There is no shortest route, as it can go round a negative cycle any number of times."
+        );
+    }
+}
@@ -1,338 +1,501 @@
 use crate::construct_graph::*;
+use crate::error::DijkstraError;
+use crate::heuristic::Heuristic;
 use crate::parse_input::*;
+use crate::route::{Route, RouteOutcome};
+use crate::search_context::{FrontierEntry, Node, SearchContext};
+use crate::shortest_path_tree::ShortestPathTree;
+use crate::weight::Weight;
 
-pub(crate) struct PathFinder {
-    pub(crate) graph: Graph,
-    pub(crate) routes_to_find: Vec<(usize, usize)>,
-    pub(crate) current_route_finding: usize,
-    pub(crate) nodes_visited: Vec<Node>,
-    pub(crate) current_connections: Vec<Vec<Edge>>,
-    pub(crate) edges_can_traverse: Vec<Edge>,
-    pub(crate) solutions: Vec<String>,
+use std::sync::Arc;
+
+/// Which search is used to find each route
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub enum Algorithm<'a, W = usize> {
+    Dijkstra,
+    /// A* guided by the heuristic, see `SearchContext::a_star`
+    AStar(&'a dyn Heuristic<W>),
+    /// see `SearchContext::bidirectional_dijkstra`
+    Bidirectional,
+    /// allows negative weights, see `SearchContext::bellman_ford`. In batch mode the text format
+    /// is read with signed weights.
+    BellmanFord,
+    /// for graphs whose edges all have the same weight, see `SearchContext::bfs`
+    Bfs,
+    /// for graphs whose edges are all 0 or the same weight, see `SearchContext::zero_one_bfs`
+    ZeroOneBfs,
 }
 
-#[derive(Debug, Clone, PartialEq, Copy)]
-pub(crate) struct Node {
-    pub index: usize,
-    pub parent_idx: usize,
-    pub dist_to_node: usize,
+/// A graph and the routes to find in it, with an error for each route that couldn't be read,
+/// for `PathFinder::solve_batch`
+pub type RouteBatch<W = usize> = (Graph<W>, Vec<Result<RouteQuery, DijkstraError>>);
+
+pub struct PathFinder<W = usize> {
+    pub(crate) graph: Arc<Graph<W>>,
+    pub(crate) routes_to_find: Vec<(usize, usize)>,
+    pub current_route_finding: usize,
+    pub(crate) search: SearchContext<W>,
+    pub solutions: Vec<Result<Route<W>, DijkstraError>>,
 }
 
-impl Node {
-    pub(crate) fn new(index_: usize, parent_idx_: usize, dist_to_node_: usize) -> Node {
-        return Node {
-            index: index_,
-            parent_idx: parent_idx_,
-            dist_to_node: dist_to_node_,
-        };
+impl PathFinder {
+    /// create the PathFinder struct directly from the problem input, with integer weights
+    pub fn new_from_string(contents: &str) -> Result<PathFinder, DijkstraError> {
+        return PathFinder::new_from_string_with_weights(contents);
+    }
+
+    /// batch mode with integer weights, see `solve_batch_from_string_with_weights`
+    pub fn solve_batch_from_string(
+        contents: &str,
+        heuristic: Option<&dyn Heuristic>,
+    ) -> Result<Vec<RouteOutcome>, DijkstraError> {
+        return PathFinder::solve_batch_from_string_with_weights(contents, heuristic);
     }
 }
 
-impl PathFinder {
-    pub(crate) fn new(graph: Graph, routes_to_find: Vec<(usize, usize)>) -> PathFinder {
+impl<W: Weight> PathFinder<W> {
+    /// the graph can be passed by value, or as an `Arc<Graph>` shared with other PathFinders.
+    /// Every route must start and end at a node of the graph.
+    pub fn new(
+        graph: impl Into<Arc<Graph<W>>>,
+        routes_to_find: Vec<(usize, usize)>,
+    ) -> Result<PathFinder<W>, DijkstraError> {
+        let graph = graph.into();
+        for &(start_idx, end_idx) in &routes_to_find {
+            for node_idx in [start_idx, end_idx] {
+                if node_idx >= graph.number_of_nodes {
+                    return Err(DijkstraError::InvalidNodeIndex { node_idx });
+                }
+            }
+        }
         let current_route_finding = 0;
         let solutions = Vec::with_capacity(routes_to_find.len());
+        let search = SearchContext::new(graph.number_of_nodes);
 
-        // set initial connections between nodes as the connections in the immutable graph
-        let mut initial_connections: Vec<Vec<Edge>> = Vec::with_capacity(graph.number_of_nodes);
-        for _ in 0..graph.number_of_nodes {
-            initial_connections.push(Vec::with_capacity(graph.number_of_nodes));
-        }
-        for edge in &graph.edges {
-            Self::update_edge_in_both_directions(&mut initial_connections, *edge);
-        }
-
-        return PathFinder {
+        return Ok(PathFinder {
             graph,
             routes_to_find,
             current_route_finding,
-            nodes_visited: vec![],
-            current_connections: initial_connections,
-            edges_can_traverse: vec![],
+            search,
             solutions,
-        };
+        });
     }
 
-    /// create the PathFinder struct directly from the problem input
-    pub(crate) fn new_from_string(contents: &str) -> Result<PathFinder, String> {
-        let graph = Graph::new_from_string(contents)?;
-        let (_, _, routes_str) = split_contents_into_nodes_edges_routes(contents.to_string())?;
-        let routes_to_find = parse_routes_from_string(&routes_str, &graph.graph_nodes)?;
-        return Ok(PathFinder::new(graph, routes_to_find));
+    pub fn graph(&self) -> &Graph<W> {
+        return &self.graph;
     }
 
-    pub fn dijkstra(&mut self) -> Result<(usize, Vec<usize>), String> {
-        // ensure the path finder is clean to use
-        // todo: this shouldn't be the concern of the dijkstra fn
-        self.mark_all_edges_as_not_traversed();
-        self.reset_nodes_visited();
+    pub fn routes_to_find(&self) -> &[(usize, usize)] {
+        return &self.routes_to_find;
+    }
 
-        // determine which route to find
-        let (original_start_idx, end_idx) = self.routes_to_find[self.current_route_finding];
-        let mut current_idx = original_start_idx;
+    /// create the PathFinder struct directly from the problem input, with weights of type W
+    pub fn new_from_string_with_weights(contents: &str) -> Result<PathFinder<W>, DijkstraError> {
+        return PathFinder::new_from_string_allowing_negative_weights(contents, false);
+    }
 
-        // current node has no starting point, so parent = itself.
-        self.nodes_visited[current_idx] = Node::new(current_idx, current_idx, 0);
+    /// as new_from_string_with_weights, but negative weights are allowed, for use with
+    /// `bellman_ford`
+    pub fn new_from_string_with_signed_weights(
+        contents: &str,
+    ) -> Result<PathFinder<W>, DijkstraError> {
+        return PathFinder::new_from_string_allowing_negative_weights(contents, true);
+    }
 
-        loop {
-            // add all edges that we're connecting to for the first time into consideration for the edges to traverse next
-            self.add_to_frontier_edges_from_node(current_idx);
+    fn new_from_string_allowing_negative_weights(
+        contents: &str,
+        allow_negative_weights: bool,
+    ) -> Result<PathFinder<W>, DijkstraError> {
+        let graph =
+            Graph::new_from_string_allowing_negative_weights(contents, allow_negative_weights)?;
+        let (node_data, edge_data, routes_str) =
+            split_contents_into_nodes_edges_routes(contents.to_string())?;
+        let (_, lines_before_routes) = lines_before_edges_and_routes(&node_data, &edge_data);
+        let routes_to_find = parse_routes_from_string(&routes_str, &graph.graph_nodes)
+            .map_err(|e| e.offset_line(lines_before_routes))?;
+        return PathFinder::new(graph, routes_to_find);
+    }
 
-            if self.edges_can_traverse.is_empty() {
-                if self.nodes_visited.iter().find(|&x| x.index == end_idx) == None {
-                    return Err("Are the start and end disconnected? No path found".to_string());
-                } else {
-                    let nodes_in_order = self.get_route_travelled();
-                    return Ok((self.nodes_visited[end_idx].dist_to_node, nodes_in_order));
-                }
-            }
-            let new_edge_connected = self.traverse_shortest_connected_edge();
-            if new_edge_connected != None {
-                current_idx = new_edge_connected
-                    .expect("Expected an edge index to be returned from edge traversal");
-            }
-        }
+    /// Batch mode: parse the input and find every route in it, with or without a heuristic.
+    /// A route followed by a number, i.e. `A B 3`, gets up to that many of its shortest routes.
+    /// Only a problem with the nodes or edges is an error, every line of the routes gets its
+    /// own outcome, in the order the routes are given.
+    pub fn solve_batch_from_string_with_weights(
+        contents: &str,
+        heuristic: Option<&dyn Heuristic<W>>,
+    ) -> Result<Vec<RouteOutcome<W>>, DijkstraError> {
+        let algorithm = match heuristic {
+            Some(heuristic) => Algorithm::AStar(heuristic),
+            None => Algorithm::Dijkstra,
+        };
+        return PathFinder::solve_batch_from_string_using(contents, algorithm);
     }
 
-    /// loops through all of the routes to be found to support inputs with multiple routes
-    pub(crate) fn dijkstra_multiple_routes(&mut self) -> Result<(), String> {
-        while self.current_route_finding < self.routes_to_find.len() {
-            let (dist, nodes_in_order) = self.dijkstra()?;
-            self.solutions.push(format!(
-                "{}, dist {}",
-                self.human_readable_route(nodes_in_order)?,
-                dist
-            ));
-            self.current_route_finding += 1;
-        }
-        return Ok(());
+    /// batch mode, finding every route with the given algorithm
+    pub fn solve_batch_from_string_using(
+        contents: &str,
+        algorithm: Algorithm<W>,
+    ) -> Result<Vec<RouteOutcome<W>>, DijkstraError> {
+        let allow_negative_weights = matches!(algorithm, Algorithm::BellmanFord);
+        let (graph, route_queries) =
+            PathFinder::read_batch_allowing_negative_weights(contents, allow_negative_weights)?;
+        return Ok(PathFinder::solve_batch(graph, route_queries, algorithm));
     }
 
-    /// if we do want to find multiple paths, reset the PathFinder
-    pub fn reset_nodes_visited(&mut self) {
-        let number_of_nodes = self.graph.graph_nodes.len();
-        self.nodes_visited = Vec::with_capacity(number_of_nodes);
-        for _ in 0..number_of_nodes {
-            self.nodes_visited
-                .push(Node::new(INFINITE_DIST, INFINITE_DIST, 0));
-        }
+    /// the graph and the routes of the text format, for `solve_batch`. Only a problem with the
+    /// nodes or edges is an error, every line of the routes gets its own result.
+    pub fn read_batch_from_string(contents: &str) -> Result<RouteBatch<W>, DijkstraError> {
+        return PathFinder::read_batch_allowing_negative_weights(contents, false);
     }
 
-    /// if we do want to find multiple paths, reset the PathFinder
-    pub(crate) fn mark_all_edges_as_not_traversed(&mut self) {
-        // each index into current_connections is a vector of edges to that node
-        for node_idx in self.current_connections.iter_mut() {
-            for edge in node_idx.iter_mut() {
-                edge.is_traversed = false;
-            }
-        }
+    /// as read_batch_from_string, but negative weights are allowed, for use with `bellman_ford`
+    pub fn read_batch_from_string_with_signed_weights(
+        contents: &str,
+    ) -> Result<RouteBatch<W>, DijkstraError> {
+        return PathFinder::read_batch_allowing_negative_weights(contents, true);
     }
 
-    /// select shortest edge that is connected to a node in the tree
-    pub fn traverse_shortest_connected_edge(&mut self) -> Option<usize> {
-        let closest_edge = self.edges_can_traverse.next_edge_to_traverse();
-        self.mark_edge_as_traversed(closest_edge);
+    fn read_batch_allowing_negative_weights(
+        contents: &str,
+        allow_negative_weights: bool,
+    ) -> Result<RouteBatch<W>, DijkstraError> {
+        let graph =
+            Graph::new_from_string_allowing_negative_weights(contents, allow_negative_weights)?;
+        let (node_data, edge_data, routes_str) =
+            split_contents_into_nodes_edges_routes(contents.to_string())?;
+        let (_, lines_before_routes) = lines_before_edges_and_routes(&node_data, &edge_data);
+        let route_queries = parse_route_queries_from_string(&routes_str, &graph.graph_nodes)
+            .into_iter()
+            .map(|query| query.map_err(|e| e.offset_line(lines_before_routes)))
+            .collect();
+        return Ok((graph, route_queries));
+    }
 
-        match self
-            .nodes_visited
+    /// find every route that could be parsed with the given algorithm, giving the routes that
+    /// couldn't be parsed their error as their outcome
+    pub fn solve_batch(
+        graph: Graph<W>,
+        route_queries: Vec<Result<RouteQuery, DijkstraError>>,
+        algorithm: Algorithm<W>,
+    ) -> Vec<RouteOutcome<W>> {
+        let routes_to_find = route_queries
             .iter()
-            .find(|&x| x.index == closest_edge.index_second)
-        {
-            // if we haven't been to this node before, add the node
-            None => {
-                self.nodes_visited[closest_edge.index_second] = Node::new(
-                    closest_edge.index_second,
-                    closest_edge.index_first,
-                    self.nodes_visited[closest_edge.index_first].dist_to_node + closest_edge.weight,
-                );
-                return Some(closest_edge.index_second);
-            }
-            // if we have been to this node before, check if the node has been found with a shorter path
-            // if so, update all routes that used the previous connection to use the new connection
-            Some(_) => {
-                let dist_dec = self.nodes_visited.update_path_with_new_edge(closest_edge);
-                if dist_dec != 0 {
-                    // this function recursively updates all nodes that are before the node with an improved connection
-                    // we've already updated the current node, so we don't care about the distance to that node
-                    // and using the Some(node) causes issues with mutability, so disguise an edge as a node
-                    self.nodes_visited.update_paths_through_node(
-                        Node::new(
-                            closest_edge.index_second,
-                            closest_edge.index_first,
-                            INFINITE_DIST,
-                        ),
-                        dist_dec,
-                    );
+            .filter_map(|query| query.as_ref().ok())
+            .map(|query| (query.start_idx, query.end_idx))
+            .collect();
+        // every route that could be parsed is in the graph
+        let mut path_finder = PathFinder::new(graph, routes_to_find)
+            .expect("Expected the parsed routes to be nodes of the graph");
+
+        let mut outcomes = Vec::with_capacity(route_queries.len());
+        for query in route_queries {
+            let query = match query {
+                Ok(query) => query,
+                Err(e) => {
+                    outcomes.push(RouteOutcome::new(Err(e)));
+                    continue;
                 }
-                return None;
-            }
+            };
+            // the routes to find are in the same order as the routes that could be parsed
+            let outcome = match query.number_of_routes {
+                Some(k) => RouteOutcome::new_from_routes(path_finder.k_shortest_routes(k)),
+                None => {
+                    let solution =
+                        path_finder
+                            .find_route(algorithm)
+                            .and_then(|(_, nodes_in_order)| {
+                                Route::new(&path_finder.graph, nodes_in_order)
+                            });
+                    RouteOutcome::new(solution)
+                }
+            };
+            outcomes.push(outcome);
+            path_finder.current_route_finding += 1;
         }
+        return outcomes;
     }
 
-    fn update_edge_in_both_directions(current_connections: &mut Vec<Vec<Edge>>, new_edge: Edge) {
-        let new_edge_is_updated = Self::update_existing_edge(current_connections, new_edge);
-        // same in reverse, assuming bidirectionality of edges
-        if new_edge_is_updated {
-            let new_reverse_edge =
-                Edge::new(new_edge.index_second, new_edge.index_first, new_edge.weight);
-            Self::update_existing_edge(current_connections, new_reverse_edge);
-        }
+    /// find the current route, settling nodes in order of their distance from the start
+    pub fn dijkstra(&mut self) -> Result<(W, Vec<usize>), DijkstraError> {
+        let (start_idx, end_idx) = self.routes_to_find[self.current_route_finding];
+        return self.search.dijkstra(&self.graph, start_idx, end_idx);
     }
 
-    /// if the input has the edge a->b more than once, choose the minimum weight
-    fn update_existing_edge(current_connections: &mut Vec<Vec<Edge>>, new_edge: Edge) -> bool {
-        let start_index = new_edge.index_first;
-        let edge_index = current_connections[start_index]
-            .iter()
-            .position(|x| x.index_second == new_edge.index_second);
-        let mut edge_was_updated = true;
-        match edge_index {
-            None => {}
-            Some(idx_into_edge_list) => {
-                let old_edge_weight = current_connections[start_index][idx_into_edge_list].weight;
-                if old_edge_weight >= new_edge.weight {
-                    current_connections[start_index].remove(idx_into_edge_list);
-                } else {
-                    edge_was_updated = false;
-                }
-            }
-        }
-        current_connections[start_index].push(new_edge);
-        return edge_was_updated;
+    /// the shortest routes from the start of the current route to every node, see
+    /// `SearchContext::shortest_path_tree`
+    pub fn shortest_path_tree(&mut self) -> ShortestPathTree<W> {
+        let (start_idx, _) = self.routes_to_find[self.current_route_finding];
+        return self.search.shortest_path_tree(&self.graph, start_idx);
     }
 
-    /// mark edge as traversed to avoid traversing the same edge more than once
-    pub(crate) fn mark_edge_as_traversed(&mut self, edge: Edge) {
-        for e in self.current_connections[edge.index_first].iter_mut() {
-            if e.index_second == edge.index_second && e.index_first == edge.index_first {
-                e.is_traversed = true;
-                break;
-            }
-        }
+    /// find the current route with A*, see `SearchContext::a_star`
+    pub fn a_star(
+        &mut self,
+        heuristic: &dyn Heuristic<W>,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        let (start_idx, end_idx) = self.routes_to_find[self.current_route_finding];
+        return self
+            .search
+            .a_star(&self.graph, start_idx, end_idx, heuristic);
     }
 
-    /// when adding a previously unconnected node to the tree, all edges from this node now become traversable
-    fn add_to_frontier_edges_from_node(&mut self, edge_start_idx: usize) {
-        for edge in &self.current_connections[edge_start_idx] {
-            if !edge.is_traversed && !self.edges_can_traverse.contains(&edge) {
-                self.edges_can_traverse.push(*edge);
-            }
-        }
+    /// find the current route by searching from both ends at once, see
+    /// `SearchContext::bidirectional_dijkstra`
+    pub fn bidirectional_dijkstra(&mut self) -> Result<(W, Vec<usize>), DijkstraError> {
+        let (start_idx, end_idx) = self.routes_to_find[self.current_route_finding];
+        return self
+            .search
+            .bidirectional_dijkstra(&self.graph, start_idx, end_idx);
     }
 
-    /// go backwards through the nodes to find the parent node.
-    fn get_route_travelled(&self) -> Vec<usize> {
-        let original_start_idx = self.routes_to_find[self.current_route_finding].0;
-        let end_idx = self.routes_to_find[self.current_route_finding].1;
-        let mut idx = end_idx;
-        let mut nodes_in_order: Vec<usize> = Vec::new();
+    /// find the current route when some edge weights may be negative, see
+    /// `SearchContext::bellman_ford`
+    pub fn bellman_ford(&mut self) -> Result<(W, Vec<usize>), DijkstraError> {
+        let (start_idx, end_idx) = self.routes_to_find[self.current_route_finding];
+        return self.search.bellman_ford(&self.graph, start_idx, end_idx);
+    }
 
-        nodes_in_order.push(end_idx);
-        while idx != original_start_idx {
-            idx = self.nodes_visited[idx].parent_idx;
-            nodes_in_order.push(idx);
-        }
+    /// find the current route with a breadth first search, see `SearchContext::bfs`
+    pub fn bfs(&mut self) -> Result<(W, Vec<usize>), DijkstraError> {
+        let (start_idx, end_idx) = self.routes_to_find[self.current_route_finding];
+        return self.search.bfs(&self.graph, start_idx, end_idx);
+    }
 
-        nodes_in_order.reverse();
+    /// find the current route with a 0-1 breadth first search, see `SearchContext::zero_one_bfs`
+    pub fn zero_one_bfs(&mut self) -> Result<(W, Vec<usize>), DijkstraError> {
+        let (start_idx, end_idx) = self.routes_to_find[self.current_route_finding];
+        return self.search.zero_one_bfs(&self.graph, start_idx, end_idx);
+    }
 
-        return nodes_in_order;
+    /// up to k loopless routes for the current route, shortest first, see
+    /// `SearchContext::k_shortest_paths`
+    pub fn k_shortest_routes(&mut self, k: usize) -> Result<Vec<Route<W>>, DijkstraError> {
+        let (start_idx, end_idx) = self.routes_to_find[self.current_route_finding];
+        return self
+            .search
+            .k_shortest_paths(&self.graph, start_idx, end_idx, k)?
+            .into_iter()
+            .map(|(_, nodes_in_order)| Route::new(&self.graph, nodes_in_order))
+            .collect();
     }
 
-    pub fn human_readable_route(&self, nodes_in_order: Vec<usize>) -> Result<String, String> {
-        let mut path_travelled: Vec<String> = Vec::new();
-        for node_idx in nodes_in_order {
-            let node = &self.graph.graph_nodes[node_idx];
-            if node.index != node_idx {
-                return Err("Error in the indexing for the route travelled.".to_string());
-            } else {
-                path_travelled.push(node.node_name.to_string());
-            }
+    /// find the current route with the given algorithm
+    pub fn find_route(
+        &mut self,
+        algorithm: Algorithm<W>,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        match algorithm {
+            Algorithm::Dijkstra => return self.dijkstra(),
+            Algorithm::AStar(heuristic) => return self.a_star(heuristic),
+            Algorithm::Bidirectional => return self.bidirectional_dijkstra(),
+            Algorithm::BellmanFord => return self.bellman_ford(),
+            Algorithm::Bfs => return self.bfs(),
+            Algorithm::ZeroOneBfs => return self.zero_one_bfs(),
         }
-        let mut final_path: String = path_travelled[0].to_string();
-        for i in 1..path_travelled.len() {
-            final_path = format!("{}->{}", final_path, path_travelled[i]);
+    }
+
+    /// loops through all of the routes to be found to support inputs with multiple routes.
+    /// Each route gets its own result in solutions, so a disconnected route does not stop the
+    /// routes after it from being found.
+    pub fn find_multiple_routes(&mut self, algorithm: Algorithm<W>) {
+        while self.current_route_finding < self.routes_to_find.len() {
+            let solution = self
+                .find_route(algorithm)
+                .and_then(|(_, nodes_in_order)| Route::new(&self.graph, nodes_in_order));
+            self.solutions.push(solution);
+            self.current_route_finding += 1;
         }
+    }
 
-        return Ok(final_path);
+    /// find every route with dijkstra, see find_multiple_routes
+    pub fn dijkstra_multiple_routes(&mut self) {
+        self.find_multiple_routes(Algorithm::Dijkstra);
+    }
+
+    /// as dijkstra_multiple_routes, but each route is found with bidirectional_dijkstra
+    pub fn bidirectional_dijkstra_multiple_routes(&mut self) {
+        self.find_multiple_routes(Algorithm::Bidirectional);
     }
-}
 
-trait UpdatePath {
-    fn update_path_with_new_edge(&mut self, closest_edge: Edge) -> usize;
-    fn update_paths_through_node(&mut self, closest_node: Node, decrease_in_dist: usize);
+    /// as dijkstra_multiple_routes, but each route is found with A* using the given heuristic
+    pub fn a_star_multiple_routes(&mut self, heuristic: &dyn Heuristic<W>) {
+        self.find_multiple_routes(Algorithm::AStar(heuristic));
+    }
 }
 
-impl UpdatePath for Vec<Node> {
-    /// if we have a path to a node, but a new edge provides a better one, update the path to that node.
-    fn update_path_with_new_edge(&mut self, closest_edge: Edge) -> usize {
-        let node_in_current_path = self[closest_edge.index_second];
+impl<W: Weight> SearchContext<W> {
+    /// find the shortest route from start to end, settling nodes in order of their distance from the start
+    pub fn dijkstra(
+        &mut self,
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        // with no estimate of the distance remaining, A* settles nodes in the same order as dijkstra
+        return self.a_star(graph, start_idx, end_idx, &|_, _| W::zero());
+    }
 
-        let node_visited_already = self
-            .into_iter()
-            .find(|x| x.index == closest_edge.index_first);
-
-        match node_visited_already {
-            Some(node) => {
-                if node_in_current_path.dist_to_node > node.dist_to_node + closest_edge.weight {
-                    let decrease_in_dist = node_in_current_path.dist_to_node
-                        - (node.dist_to_node + closest_edge.weight);
-                    self[closest_edge.index_second] = Node::new(
-                        closest_edge.index_second,
-                        node.index,
-                        closest_edge.weight + node.dist_to_node,
-                    );
-                    return decrease_in_dist;
+    /// A* search for the route, guided by a heuristic estimate of the distance to the end.
+    /// Nodes are settled in order of distance travelled + estimated distance remaining,
+    /// so far fewer nodes are settled than with dijkstra when the heuristic is informative.
+    pub fn a_star(
+        &mut self,
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+        heuristic: &dyn Heuristic<W>,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        return self.a_star_along(graph, start_idx, end_idx, heuristic, &|_| true);
+    }
+
+    /// as a_star, but only travelling along the edges that `is_usable` allows
+    pub(crate) fn a_star_along(
+        &mut self,
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+        heuristic: &dyn Heuristic<W>,
+        is_usable: &dyn Fn(&Edge<W>) -> bool,
+    ) -> Result<(W, Vec<usize>), DijkstraError> {
+        self.begin_query(graph.number_of_nodes);
+
+        // start node has no starting point, so parent = itself.
+        self.visit(Node::new(start_idx, start_idx, W::zero()));
+        self.frontier.push(FrontierEntry {
+            priority: heuristic.estimate(start_idx, end_idx),
+            dist_to_node: W::zero(),
+            node_idx: start_idx,
+        });
+
+        // paths whose distance overflows are dropped. They are longer than any path that can be
+        // represented, so they only matter if the end can't be reached any other way, in which
+        // case the end may have been reachable through them and it is reported as an overflow.
+        let mut has_overflowed = false;
+        while let Some(entry) = self.frontier.pop() {
+            let current_idx = entry.node_idx;
+            // a shorter path to this node was found after this entry was added
+            if Some(entry.dist_to_node) > self.dist_to(current_idx) {
+                continue;
+            }
+            if current_idx == end_idx {
+                return Ok((entry.dist_to_node, self.route_to(start_idx, end_idx)));
+            }
+
+            for edge in graph.connections[current_idx]
+                .iter()
+                .filter(|edge| is_usable(edge))
+            {
+                let next_idx = edge.index_second;
+                let dist_through_current = match entry.dist_to_node.checked_add(edge.weight) {
+                    Some(dist) => dist,
+                    None => {
+                        has_overflowed = true;
+                        continue;
+                    }
+                };
+                if let Some(dist_to_next) = self.dist_to(next_idx) {
+                    if dist_to_next <= dist_through_current {
+                        continue;
+                    }
                 }
+                self.visit(Node::new(next_idx, current_idx, dist_through_current));
+                self.frontier.push(FrontierEntry {
+                    // an estimate that overflows only pushes the node to the back of the frontier
+                    priority: dist_through_current
+                        .checked_add(heuristic.estimate(next_idx, end_idx))
+                        .unwrap_or_else(W::infinity),
+                    dist_to_node: dist_through_current,
+                    node_idx: next_idx,
+                });
             }
-            None => {}
         }
-        return 0;
-    }
-
-    /// if we have updated the node's path, update the path of other nodes that will be affected
-    fn update_paths_through_node(&mut self, closest_node: Node, decrease_in_dist: usize) {
-        // todo: this copy was because of mutability reasons
-        let cp = self.clone();
-        for node in cp {
-            if node.parent_idx == closest_node.index && node.dist_to_node != 0 {
-                self[node.index] = Node::new(
-                    node.index,
-                    node.parent_idx,
-                    self[node.index].dist_to_node - decrease_in_dist,
-                );
-                self.update_paths_through_node(self[node.index], decrease_in_dist);
-            }
+
+        if has_overflowed {
+            return Err(DijkstraError::distance_overflow(graph, start_idx, end_idx));
         }
-        return;
+        return Err(DijkstraError::disconnected(graph, start_idx, end_idx));
     }
-}
-
-trait UpdateEdge {
-    fn next_edge_to_traverse(&mut self) -> Edge;
-}
 
-impl UpdateEdge for Vec<Edge> {
-    fn next_edge_to_traverse(&mut self) -> Edge {
-        let mut min_weight = INFINITE_DIST;
-        let mut idx_edge = 0;
+    /// find the shortest distance to every node that can be reached from the start, without
+    /// stopping at an end node. Nodes that can only be reached by a path whose distance overflows
+    /// are left unreached. Returns the reached nodes in the order they were settled, so every
+    /// node comes after its parent.
+    pub(crate) fn dijkstra_from(&mut self, graph: &Graph<W>, start_idx: usize) -> Vec<usize> {
+        return self.dijkstra_from_sources(graph, &[start_idx], false);
+    }
 
-        // todo: keep this in a sorted struct to minimise comparisons
-        for idx in 0..self.len() {
-            if self[idx].weight < min_weight {
-                min_weight = self[idx].weight;
-                idx_edge = idx;
+    /// as dijkstra_from, but starting from every one of the sources at a distance of 0, so each
+    /// node is reached from the source closest to it. Going backwards, the edges are followed
+    /// the wrong way round, so each node gets the distance from it to the closest source, and
+    /// its parent is the node after it on the route there.
+    pub(crate) fn dijkstra_from_sources(
+        &mut self,
+        graph: &Graph<W>,
+        sources: &[usize],
+        is_backwards: bool,
+    ) -> Vec<usize> {
+        self.begin_query(graph.number_of_nodes);
+        for &source_idx in sources {
+            // a repeated source would otherwise be settled twice
+            if self.dist_to(source_idx).is_some() {
+                continue;
             }
+            self.visit(Node::new(source_idx, source_idx, W::zero()));
+            self.frontier.push(FrontierEntry {
+                priority: W::zero(),
+                dist_to_node: W::zero(),
+                node_idx: source_idx,
+            });
         }
-        let edge_to_travel = self[idx_edge];
-        self.remove(idx_edge);
 
-        return edge_to_travel;
+        let mut settled = Vec::new();
+        while let Some(entry) = self.frontier.pop() {
+            let current_idx = entry.node_idx;
+            if Some(entry.dist_to_node) > self.dist_to(current_idx) {
+                continue;
+            }
+            settled.push(current_idx);
+
+            let edges = if is_backwards {
+                &graph.reverse_connections[current_idx]
+            } else {
+                &graph.connections[current_idx]
+            };
+            for edge in edges {
+                let next_idx = if is_backwards {
+                    edge.index_first
+                } else {
+                    edge.index_second
+                };
+                let dist_through_current = match entry.dist_to_node.checked_add(edge.weight) {
+                    Some(dist) => dist,
+                    None => continue,
+                };
+                if let Some(dist_to_next) = self.dist_to(next_idx) {
+                    if dist_to_next <= dist_through_current {
+                        continue;
+                    }
+                }
+                self.visit(Node::new(next_idx, current_idx, dist_through_current));
+                self.frontier.push(FrontierEntry {
+                    priority: dist_through_current,
+                    dist_to_node: dist_through_current,
+                    node_idx: next_idx,
+                });
+            }
+        }
+        return settled;
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::heuristic::StraightLineHeuristic;
+    use crate::weight::TotalF64;
+
+    use std::fs;
 
     #[test]
     fn test_dijkstra() {
@@ -343,8 +506,9 @@ mod tests {
                 GraphNode::new(2, "node2".to_string()),
             ],
             vec![Edge::new(0, 1, 2), Edge::new(1, 2, 3)],
-        );
-        let mut pf = PathFinder::new(graph, vec![(0, 2)]);
+        )
+        .unwrap();
+        let mut pf = PathFinder::new(graph, vec![(0, 2)]).unwrap();
         let (dist, path) = pf.dijkstra().unwrap();
         assert_eq!(dist, 5);
         assert_eq!(path, vec![0, 1, 2]);
@@ -355,7 +519,7 @@ mod tests {
             Graph::new_from_string("3\nA\nB\nC\n\n5\nA B 20\nA B 2\nB A 2\nB C 3\nC B 1\n\nA C")
                 .unwrap();
 
-        let mut pf = PathFinder::new(graph, vec![(0, 2)]);
+        let mut pf = PathFinder::new(graph, vec![(0, 2)]).unwrap();
         let (dist, path) = pf.dijkstra().unwrap();
         assert_eq!(dist, 3);
         assert_eq!(path, vec![0, 1, 2]);
@@ -371,9 +535,10 @@ mod tests {
                 GraphNode::new(2, "node2".to_string()),
             ],
             vec![Edge::new(0, 1, 2), Edge::new(1, 2, 2)],
-        );
+        )
+        .unwrap();
 
-        let mut pf = PathFinder::new(graph, vec![(0, 2)]);
+        let mut pf = PathFinder::new(graph, vec![(0, 2)]).unwrap();
         let (dist, path) = pf.dijkstra().unwrap();
         assert_eq!(dist, 4);
         assert_eq!(path, vec![0, 1, 2])
@@ -395,8 +560,9 @@ mod tests {
                 Edge::new(3, 1, 9),
                 Edge::new(3, 4, 1),
             ],
-        );
-        let mut pf = PathFinder::new(graph, vec![(0, 4)]);
+        )
+        .unwrap();
+        let mut pf = PathFinder::new(graph, vec![(0, 4)]).unwrap();
         let (dist, path) = pf.dijkstra().unwrap();
         assert_eq!(path, vec![0, 1, 2, 3, 4]);
         assert_eq!(dist, 18);
@@ -406,7 +572,7 @@ mod tests {
     fn find_correct_route_in_file() {
         let graph = Graph::new_from_string("5\nCardiff\nBristol\nLondon\nYork\nBirmingham\n\n5\nYork London 194\nCardiff Bristol 44\nBristol Birmingham 88\nBristol London 114\nBirmingham London 111\n\nCardiff London").unwrap();
 
-        let mut pf = PathFinder::new(graph, vec![(0, 2)]);
+        let mut pf = PathFinder::new(graph, vec![(0, 2)]).unwrap();
         let (dist, path) = pf.dijkstra().unwrap();
         assert_eq!(dist, 158);
         assert_eq!(path, vec![0, 1, 2]);
@@ -417,7 +583,7 @@ mod tests {
             Graph::new_from_string("4\nA\nB\nC\nD\n\n4\nA B 1\nB D 10\nA C 2\nC D 5\n\nD A")
                 .unwrap();
 
-        let mut pf = PathFinder::new(graph, vec![(3, 0)]);
+        let mut pf = PathFinder::new(graph, vec![(3, 0)]).unwrap();
         let (dist, path) = pf.dijkstra().unwrap();
 
         assert_eq!(dist, 7);
@@ -439,23 +605,26 @@ mod tests {
         let mut pf = PathFinder::new_from_string("8\nInverness\nGlasgow\nEdinburgh\nNewcastle\nManchester\nYork\nBirmingham\nLondon\n\n12\nInverness Glasgow 167\nInverness Edinburgh 158\nGlasgow Edinburgh 45\nGlasgow Newcastle 145\nGlasgow Manchester 214\nEdinburgh Newcastle 107\nNewcastle York 82\nManchester York 65\nManchester Birmingham 81\nYork Birmingham 129\nYork London 194\nBirmingham London 111\n\nLondon Edinburgh").unwrap();
         let (dist, path) = pf.dijkstra().unwrap();
         assert_eq!(path, [7, 5, 3, 2]);
-        assert_eq!(pf.nodes_visited[7].dist_to_node, 0);
-        assert_eq!(pf.nodes_visited[5].dist_to_node, 194);
-        assert_eq!(pf.nodes_visited[3].index, 3);
-        assert_eq!(pf.nodes_visited[3].parent_idx, 5);
-        assert_eq!(pf.nodes_visited[3].dist_to_node, 194 + 82);
-        assert_eq!(pf.nodes_visited[2].parent_idx, 3);
-        assert_eq!(pf.nodes_visited[2].dist_to_node, 194 + 82 + 107);
+        assert_eq!(pf.search.nodes_visited[7].unwrap().dist_to_node, 0);
+        assert_eq!(pf.search.nodes_visited[5].unwrap().dist_to_node, 194);
+        assert_eq!(pf.search.nodes_visited[3].unwrap().index, 3);
+        assert_eq!(pf.search.nodes_visited[3].unwrap().parent_idx, 5);
+        assert_eq!(pf.search.nodes_visited[3].unwrap().dist_to_node, 194 + 82);
+        assert_eq!(pf.search.nodes_visited[2].unwrap().parent_idx, 3);
+        assert_eq!(
+            pf.search.nodes_visited[2].unwrap().dist_to_node,
+            194 + 82 + 107
+        );
 
         assert_eq!(dist, 194 + 82 + 107);
 
         let (_, path) = pf.dijkstra().unwrap();
         assert_eq!(path, [7, 5, 3, 2]);
-        assert_eq!(pf.nodes_visited[7].dist_to_node, 0);
-        assert_eq!(pf.nodes_visited[5].dist_to_node, 194);
-        assert_eq!(pf.nodes_visited[3].index, 3);
-        assert_eq!(pf.nodes_visited[3].parent_idx, 5);
-        assert_eq!(pf.nodes_visited[3].dist_to_node, 194 + 82);
+        assert_eq!(pf.search.nodes_visited[7].unwrap().dist_to_node, 0);
+        assert_eq!(pf.search.nodes_visited[5].unwrap().dist_to_node, 194);
+        assert_eq!(pf.search.nodes_visited[3].unwrap().index, 3);
+        assert_eq!(pf.search.nodes_visited[3].unwrap().parent_idx, 5);
+        assert_eq!(pf.search.nodes_visited[3].unwrap().dist_to_node, 194 + 82);
     }
     #[test]
     fn find_correct_route_in_file_when_shorter_early_edge_is_wrong_path() {
@@ -478,7 +647,11 @@ mod tests {
         let route = parse_routes_from_string(nodes_edges_routes[2], &graph.graph_nodes);
         assert_eq!(
             route,
-            Err("Route is self referential. Dist from A to A = 0".to_string())
+            Err(DijkstraError::SelfReferentialRoute {
+                line: 1,
+                text: "A A".to_string(),
+                node_name: "A".to_string()
+            })
         );
     }
     #[test]
@@ -486,140 +659,375 @@ mod tests {
         let graph = Graph::new_from_string("4\nA\nB\nC\nD\n\n4\nA B 1\nA B 2\nB C 3\nA C 4\n\nA D")
             .unwrap();
 
-        let mut pf = PathFinder::new(graph, vec![(0, 3)]);
+        let mut pf = PathFinder::new(graph, vec![(0, 3)]).unwrap();
         assert_eq!(
             pf.dijkstra(),
-            Err("Are the start and end disconnected? No path found".to_string())
+            Err(DijkstraError::Disconnected {
+                start_idx: 0,
+                end_idx: 3,
+                start_name: "A".to_string(),
+                end_name: "D".to_string()
+            })
         );
     }
     #[test]
-    fn test_updating_path_simple() {
-        let mut nodes_visited = vec![
-            Node::new(0, 0, 0),
-            Node::new(1, 0, 100),
-            Node::new(2, 0, 300),
-        ];
-        let closest_edge = Edge::new(1, 2, 20);
-        nodes_visited.update_path_with_new_edge(closest_edge);
+    fn find_multiple_paths() {
+        let mut pf = PathFinder::new_from_string("8\nInverness\nGlasgow\nEdinburgh\nNewcastle\nManchester\nYork\nBirmingham\nLondon\n\n12\nInverness Glasgow 167\nInverness Edinburgh 158\nGlasgow Edinburgh 45\nGlasgow Newcastle 145\nGlasgow Manchester 214\nEdinburgh Newcastle 107\nNewcastle York 82\nManchester York 65\nManchester Birmingham 81\nYork Birmingham 129\nYork London 194\nBirmingham London 111\n\nLondon Inverness\nInverness London").unwrap();
+
+        pf.dijkstra_multiple_routes();
         assert_eq!(
-            nodes_visited,
-            vec![
-                Node::new(0, 0, 0),
-                Node::new(1, 0, 100),
-                Node::new(2, 1, 120)
-            ]
+            pf.solutions[0].as_ref().unwrap().to_string(),
+            "London->York->Newcastle->Edinburgh->Inverness, dist 541"
+        );
+        assert_eq!(
+            pf.solutions[1].as_ref().unwrap().to_string(),
+            "Inverness->Edinburgh->Newcastle->York->London, dist 541"
+        );
+        assert_eq!(
+            pf.solutions[1].as_ref().unwrap().leg_weights,
+            vec![158, 107, 82, 194]
         );
     }
+
     #[test]
-    fn test_updating_path_repeatedly() {
-        let mut nodes_visited = vec![
-            Node::new(0, 0, 0),
-            Node::new(1, 0, 300),
-            Node::new(2, 1, 400),
-            Node::new(3, 2, 500),
-        ];
-        let closest_edge = Edge::new(0, 1, 20);
-        nodes_visited.update_path_with_new_edge(closest_edge);
-        nodes_visited.update_paths_through_node(Node::new(1, 0, 20), 280);
+    fn a_star_matches_dijkstra() {
+        let mut pf = PathFinder::new_from_string("8\nInverness\nGlasgow\nEdinburgh\nNewcastle\nManchester\nYork\nBirmingham\nLondon\n\n12\nInverness Glasgow 167\nInverness Edinburgh 158\nGlasgow Edinburgh 45\nGlasgow Newcastle 145\nGlasgow Manchester 214\nEdinburgh Newcastle 107\nNewcastle York 82\nManchester York 65\nManchester Birmingham 81\nYork Birmingham 129\nYork London 194\nBirmingham London 111\n\nLondon Inverness\nInverness London").unwrap();
+
+        // a zero heuristic reduces A* to dijkstra
+        let (dist, path) = pf.a_star(&|_, _| 0).unwrap();
+        assert_eq!(dist, 541);
+        assert_eq!(path, vec![7, 5, 3, 2, 0]);
 
+        pf.current_route_finding += 1;
+        let (dist, path) = pf.a_star(&|_, _| 0).unwrap();
+        assert_eq!((dist, path), pf.dijkstra().unwrap());
+    }
+    #[test]
+    fn a_star_with_straight_line_heuristic() {
+        // B is on the direct line from A to D, C is a detour with cheaper early edges
+        let mut pf = PathFinder::new_from_string(
+            "4\nA 0 0\nB 5 0\nC 0 8\nD 10 0\n\n4\nA B 6\nB D 6\nA C 1\nC D 14\n\nA D",
+        )
+        .unwrap();
+        let heuristic = StraightLineHeuristic::new(&pf.graph.graph_nodes);
+        let (dist, path) = pf.a_star(&heuristic).unwrap();
+        assert_eq!(dist, 12);
+        assert_eq!(path, vec![0, 1, 3]);
+        // C looks too far from D to be worth exploring
+        assert_eq!(pf.search.nodes_visited[2].unwrap().dist_to_node, 1);
+    }
+    #[test]
+    fn a_star_disconnected_route() {
+        let graph = Graph::new_from_string("4\nA\nB\nC\nD\n\n2\nA B 1\nB C 3\n\nA D").unwrap();
+        let mut pf = PathFinder::new(graph, vec![(0, 3)]).unwrap();
         assert_eq!(
-            nodes_visited,
-            vec![
-                Node::new(0, 0, 0),
-                Node::new(1, 0, 20),
-                Node::new(2, 1, 120),
-                Node::new(3, 2, 220)
-            ]
+            pf.a_star(&|_, _| 0),
+            Err(DijkstraError::Disconnected {
+                start_idx: 0,
+                end_idx: 3,
+                start_name: "A".to_string(),
+                end_name: "D".to_string()
+            })
         );
     }
     #[test]
-    fn test_updating_old_path() {
-        let mut nodes_visited = vec![
-            Node::new(0, 0, 0),
-            Node::new(1, 0, 100),
-            Node::new(2, 0, 300),
-            Node::new(3, 2, 400),
-        ];
-        let closest_edge = Edge::new(1, 2, 20);
-        nodes_visited.update_path_with_new_edge(closest_edge);
-        nodes_visited.update_paths_through_node(Node::new(2, 1, 20), 300 - (100 + 20));
+    fn share_one_graph_between_queries() {
+        let graph = Arc::new(Graph::new_from_string("8\nInverness\nGlasgow\nEdinburgh\nNewcastle\nManchester\nYork\nBirmingham\nLondon\n\n12\nInverness Glasgow 167\nInverness Edinburgh 158\nGlasgow Edinburgh 45\nGlasgow Newcastle 145\nGlasgow Manchester 214\nEdinburgh Newcastle 107\nNewcastle York 82\nManchester York 65\nManchester Birmingham 81\nYork Birmingham 129\nYork London 194\nBirmingham London 111\n\nLondon Inverness").unwrap());
+
+        // one context reused for many queries, without resetting anything in between
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        assert_eq!(context.dijkstra(&graph, 7, 0).unwrap().0, 541);
+        assert_eq!(context.dijkstra(&graph, 1, 2).unwrap().0, 45);
         assert_eq!(
-            nodes_visited,
+            context.dijkstra(&graph, 7, 0).unwrap().1,
+            vec![7, 5, 3, 2, 0]
+        );
+
+        // and queries run on other threads against the same graph
+        let handles: Vec<_> = (0..4)
+            .map(|start_idx| {
+                let graph = Arc::clone(&graph);
+                std::thread::spawn(move || {
+                    let mut pf = PathFinder::new(graph, vec![(start_idx, 7)]).unwrap();
+                    return pf.dijkstra().unwrap().0;
+                })
+            })
+            .collect();
+        let dists: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(dists, vec![541, 406, 383, 276]);
+
+        assert!(matches!(
+            PathFinder::new(graph, vec![(0, 99)]),
+            Err(DijkstraError::InvalidNodeIndex { node_idx: 99 })
+        ));
+    }
+    #[test]
+    fn find_route_along_one_way_edges() {
+        let mut pf = PathFinder::new_from_string(
+            "4\nA\nB\nC\nD\n\n4\nA -> B 1\nB -> D 1\nD -> C 1\nC <-> A 10\n\nA D\nD A",
+        )
+        .unwrap();
+        assert_eq!(pf.dijkstra(), Ok((2, vec![0, 1, 3])));
+
+        // the way back has to go round the one way system
+        pf.current_route_finding += 1;
+        assert_eq!(pf.dijkstra(), Ok((11, vec![3, 2, 0])));
+    }
+    #[test]
+    fn errors_report_line_in_file() {
+        let contents = "3\nA\nB\nC\n\n2\nA B 1\nB C 2\n\nA C\nA D";
+        assert_eq!(
+            PathFinder::new_from_string(contents).err(),
+            Some(DijkstraError::UnknownNode {
+                line: 11,
+                text: "A D".to_string(),
+                node_name: "D".to_string()
+            })
+        );
+        assert_eq!(
+            PathFinder::new_from_string(contents)
+                .err()
+                .unwrap()
+                .to_string(),
+            "Line 11: node D in \"A D\" should be present in node list."
+        );
+    }
+    #[test]
+    fn distances_larger_than_the_old_infinite_dist() {
+        let graph = Graph::new(
             vec![
-                Node::new(0, 0, 0),
-                Node::new(1, 0, 100),
-                Node::new(2, 1, 120),
-                Node::new(3, 2, 220)
-            ]
+                GraphNode::new(0, "A".to_string()),
+                GraphNode::new(1, "B".to_string()),
+                GraphNode::new(2, "C".to_string()),
+            ],
+            vec![
+                Edge::new(0, 1, 100_000_000),
+                Edge::new(1, 2, usize::MAX - 100_000_001),
+            ],
+        )
+        .unwrap();
+        let mut pf = PathFinder::new(graph, vec![(0, 2), (2, 0)]).unwrap();
+        pf.dijkstra_multiple_routes();
+        assert_eq!(
+            pf.solutions[0].as_ref().unwrap().total_distance,
+            usize::MAX - 1
+        );
+        assert_eq!(pf.solutions[1].as_ref().unwrap().nodes, vec![2, 1, 0]);
+    }
+    #[test]
+    fn overflowing_distances_are_an_error() {
+        let graph = Graph::new(
+            vec![
+                GraphNode::new(0, "A".to_string()),
+                GraphNode::new(1, "B".to_string()),
+                GraphNode::new(2, "C".to_string()),
+                GraphNode::new(3, "D".to_string()),
+            ],
+            vec![
+                Edge::new(0, 1, usize::MAX - 1),
+                Edge::new(1, 2, 2),
+                Edge::new(0, 3, usize::MAX),
+                Edge::new(3, 2, 0),
+            ],
+        )
+        .unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        // A->B->C overflows, but A->D->C fits exactly
+        assert_eq!(
+            context.dijkstra(&graph, 0, 2),
+            Ok((usize::MAX, vec![0, 3, 2]))
         );
-        let closest_edge = Edge::new(1, 2, 10);
-        nodes_visited.update_path_with_new_edge(closest_edge);
-        nodes_visited.update_paths_through_node(Node::new(2, 1, 10), 120 - (100 + 10));
 
+        let graph = Graph::new(
+            graph.graph_nodes,
+            vec![Edge::new(0, 1, usize::MAX), Edge::new(1, 2, 1)],
+        )
+        .unwrap();
+        assert_eq!(
+            context.dijkstra(&graph, 0, 2),
+            Err(DijkstraError::DistanceOverflow {
+                start_idx: 0,
+                end_idx: 2,
+                start_name: "A".to_string(),
+                end_name: "C".to_string()
+            })
+        );
+        // an estimate that overflows doesn't stop the route from being found
+        assert_eq!(
+            context.a_star(&graph, 0, 1, &|_, _| usize::MAX),
+            Ok((usize::MAX, vec![0, 1]))
+        );
+    }
+    #[test]
+    fn decimal_weights() {
+        let contents = "3\nA 0 0\nB 1 0\nC 1.5 0\n\n3\nA B 1.25\nB C 0.5\nA C 2.0\n\nA C\nA D";
+        let outcomes =
+            PathFinder::<TotalF64>::solve_batch_from_string_with_weights(contents, None).unwrap();
+        assert_eq!(outcomes[0].to_string(), "A->B->C, dist 1.75");
         assert_eq!(
-            nodes_visited,
+            outcomes[1],
+            RouteOutcome::UnknownNode {
+                line: 12,
+                node_name: "D".to_string()
+            }
+        );
+
+        let graph = Graph::<TotalF64>::new_from_string_with_weights(contents).unwrap();
+        let heuristic = StraightLineHeuristic::new(&graph.graph_nodes);
+        let mut pf = PathFinder::new(graph, vec![(0, 2)]).unwrap();
+        let (dist, path) = pf.a_star(&heuristic).unwrap();
+        assert_eq!(dist, TotalF64::new(1.75).unwrap());
+        assert_eq!(path, vec![0, 1, 2]);
+    }
+    #[test]
+    fn nan_and_negative_weights_are_rejected() {
+        assert_eq!(
+            PathFinder::<TotalF64>::new_from_string_with_weights("2\nA\nB\n\n1\nA B NaN\n\nA B")
+                .err()
+                .unwrap()
+                .to_string(),
+            "Line 6: distance between edges can't be NaN, NaN found in \"A B NaN\"."
+        );
+        assert_eq!(
+            PathFinder::<TotalF64>::new_from_string_with_weights("2\nA\nB\n\n1\nA B -0.5\n\nA B")
+                .err(),
+            Some(DijkstraError::NegativeWeight {
+                line: 6,
+                text: "A B -0.5".to_string(),
+                weight: "-0.5".to_string()
+            })
+        );
+    }
+    #[test]
+    fn bellman_ford_batch_reads_signed_weights() {
+        let contents = fs::read_to_string("src/test/rebates.txt").unwrap();
+        let outcomes: Vec<String> =
+            PathFinder::<i64>::solve_batch_from_string_using(&contents, Algorithm::BellmanFord)
+                .unwrap()
+                .iter()
+                .map(|outcome| outcome.to_string())
+                .collect();
+        assert_eq!(
+            outcomes,
             vec![
-                Node::new(0, 0, 0),
-                Node::new(1, 0, 100),
-                Node::new(2, 1, 110),
-                Node::new(3, 2, 210)
+                "Depot->Warehouse->Market->Outlet, dist 3",
+                "Depot->Warehouse->Market, dist 1",
+                "Outlet->Depot, no path found. Are the start and end disconnected?"
             ]
         );
-    }
+        assert!(matches!(
+            PathFinder::<i64>::solve_batch_from_string_using(&contents, Algorithm::Dijkstra),
+            Err(DijkstraError::NegativeWeight { line: 11, .. })
+        ));
 
+        let outcomes = PathFinder::<i64>::solve_batch_from_string_using(
+            "3\nA\nB\nC\n\n3 directed\nA B 1\nB C -2\nC B 1\n\nA C",
+            Algorithm::BellmanFord,
+        )
+        .unwrap();
+        assert_eq!(
+            outcomes[0].to_string(),
+            "There is no shortest route, as it can go round the negative cycle C->B->C any number of times."
+        );
+    }
     #[test]
-    fn test_update_path_to_start() {
-        // taken from the london->inverness test failing, but relevant indexes here are -5 for indexing reasons.
-        let mut nodes_visited = vec![
-            Node::new(0, 1, 240),
-            Node::new(1, 2, 111),
-            Node::new(2, 2, 0),
-        ];
-        let closest_edge = Edge::new(2, 0, 194);
-        let dec = nodes_visited.update_path_with_new_edge(closest_edge);
-        nodes_visited.update_paths_through_node(Node::new(0, 2, 194), dec);
+    fn batch_finds_k_shortest_routes_when_asked() {
+        let contents = fs::read_to_string("src/test/k_shortest.txt").unwrap();
+        let outcomes: Vec<String> = PathFinder::solve_batch_from_string(&contents, None)
+            .unwrap()
+            .iter()
+            .map(|outcome| outcome.to_string())
+            .collect();
         assert_eq!(
-            nodes_visited,
+            outcomes,
             vec![
-                Node::new(0, 2, 194),
-                Node::new(1, 2, 111),
-                Node::new(2, 2, 0)
+                "C->E->F->H, dist 5",
+                "1. C->E->F->H, dist 5\n2. C->E->G->H, dist 7\n3. C->D->F->H, dist 8",
+                "1. C->E->G, dist 5\n2. C->E->F->G, dist 6",
             ]
         );
     }
     #[test]
-    fn ensure_last_node_is_updated_correctly() {
-        let mut nodes_visited = vec![
-            Node::new(0, 2, 587),
-            Node::new(1, 3, 421),
-            Node::new(2, 3, 429),
-            Node::new(3, 5, 322),
-            Node::new(4, 6, 192),
-            Node::new(5, 7, 240),
-            Node::new(6, 7, 111),
-            Node::new(7, 7, 0),
-        ];
-        let closest_edge = Edge::new(7, 5, 194);
-        let dec = nodes_visited.update_path_with_new_edge(closest_edge);
-        nodes_visited.update_paths_through_node(Node::new(5, 7, 194), dec);
-
-        assert_eq!(Node::new(5, 7, 194), nodes_visited[5]);
-        assert_eq!(Node::new(2, 3, 383), nodes_visited[2]);
-        assert_eq!(Node::new(0, 2, 541), nodes_visited[0]);
+    fn batch_finds_k_shortest_routes_with_negative_weights() {
+        let contents = "4\nA\nB\nC\nD\n\n4 directed\nA B 5\nB D -4\nA C 2\nC D 0\n\nA D 2\nA D";
+        let outcomes: Vec<String> =
+            PathFinder::<i64>::solve_batch_from_string_using(contents, Algorithm::BellmanFord)
+                .unwrap()
+                .iter()
+                .map(|outcome| outcome.to_string())
+                .collect();
+        assert_eq!(
+            outcomes,
+            vec!["1. A->B->D, dist 1\n2. A->C->D, dist 2", "A->B->D, dist 1"]
+        );
     }
-
     #[test]
-    fn find_multiple_paths() {
-        let mut pf = PathFinder::new_from_string("8\nInverness\nGlasgow\nEdinburgh\nNewcastle\nManchester\nYork\nBirmingham\nLondon\n\n12\nInverness Glasgow 167\nInverness Edinburgh 158\nGlasgow Edinburgh 45\nGlasgow Newcastle 145\nGlasgow Manchester 214\nEdinburgh Newcastle 107\nNewcastle York 82\nManchester York 65\nManchester Birmingham 81\nYork Birmingham 129\nYork London 194\nBirmingham London 111\n\nLondon Inverness\nInverness London").unwrap();
-
-        pf.dijkstra_multiple_routes().unwrap();
+    fn disconnected_route_does_not_stop_the_batch() {
+        let mut pf =
+            PathFinder::new_from_string("4\nA\nB\nC\nD\n\n2\nA B 1\nB C 3\n\nA D\nA C\nD B")
+                .unwrap();
+        pf.dijkstra_multiple_routes();
         assert_eq!(
-            pf.solutions[0],
-            "London->York->Newcastle->Edinburgh->Inverness, dist 541"
+            pf.solutions,
+            vec![
+                Err(DijkstraError::Disconnected {
+                    start_idx: 0,
+                    end_idx: 3,
+                    start_name: "A".to_string(),
+                    end_name: "D".to_string()
+                }),
+                Ok(Route {
+                    start_idx: 0,
+                    end_idx: 2,
+                    nodes: vec![0, 1, 2],
+                    node_names: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+                    leg_weights: vec![1, 3],
+                    total_distance: 4,
+                }),
+                Err(DijkstraError::Disconnected {
+                    start_idx: 3,
+                    end_idx: 1,
+                    start_name: "D".to_string(),
+                    end_name: "B".to_string()
+                }),
+            ]
         );
+    }
+    #[test]
+    fn batch_gives_an_outcome_for_every_route() {
+        let contents = "5\nSelfReferential\nMultiplePathsA\nMultiplePathsB\nDisconnectedA\nDisconnectedB\n\n4\nSelfReferential SelfReferential 1\nMultiplePathsA MultiplePathsB 2\nMultiplePathsB MultiplePathsA 3\nMultiplePathsA MultiplePathsB 4\n\nSelfReferential SelfReferential\nMultiplePathsA MultiplePathsB\nDisconnectedA DisconnectedB\nDisconnectedA Nowhere\n";
+        let outcomes = PathFinder::solve_batch_from_string(contents, None).unwrap();
+        assert_eq!(outcomes.len(), 4);
         assert_eq!(
-            pf.solutions[1],
-            "Inverness->Edinburgh->Newcastle->York->London, dist 541"
+            outcomes[0],
+            RouteOutcome::SelfRoute {
+                line: 14,
+                node_name: "SelfReferential".to_string()
+            }
+        );
+        assert_eq!(
+            outcomes[1].to_string(),
+            "MultiplePathsA->MultiplePathsB, dist 2"
+        );
+        assert_eq!(
+            outcomes[2],
+            RouteOutcome::Unreachable {
+                start_name: "DisconnectedA".to_string(),
+                end_name: "DisconnectedB".to_string()
+            }
+        );
+        assert_eq!(
+            outcomes[3],
+            RouteOutcome::UnknownNode {
+                line: 17,
+                node_name: "Nowhere".to_string()
+            }
+        );
+
+        // A* gives the same outcomes
+        assert_eq!(
+            PathFinder::solve_batch_from_string(contents, Some(&|_, _| 0)).unwrap(),
+            outcomes
         );
     }
 }
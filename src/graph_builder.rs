@@ -0,0 +1,206 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::parse_input::{get_node_index_from_node_name, Edge, GraphNode};
+use crate::weight::Weight;
+
+/// Builds a `Graph` in code rather than from the text format, referring to nodes by name, i.e.
+///
+/// ```
+/// use rust_dijkstra::GraphBuilder;
+///
+/// let graph = GraphBuilder::new()
+///     .node("Cardiff")
+///     .node("Bristol")
+///     .edge("Cardiff", "Bristol", 44)
+///     .build()
+///     .unwrap();
+/// assert_eq!(graph.number_of_nodes(), 2);
+/// ```
+///
+/// For weights other than `usize`, start from `GraphBuilder::<TotalF64>::default()`.
+#[derive(Debug, Clone)]
+pub struct GraphBuilder<W = usize> {
+    graph_nodes: Vec<GraphNode>,
+    /// edges by node name, with whether they are directed if this was given explicitly
+    edges: Vec<(String, String, W, Option<bool>)>,
+    is_directed_by_default: bool,
+}
+
+impl<W> Default for GraphBuilder<W> {
+    fn default() -> GraphBuilder<W> {
+        return GraphBuilder {
+            graph_nodes: Vec::new(),
+            edges: Vec::new(),
+            is_directed_by_default: false,
+        };
+    }
+}
+
+impl GraphBuilder {
+    /// a builder for a graph with integer weights
+    pub fn new() -> GraphBuilder {
+        return GraphBuilder::default();
+    }
+}
+
+impl<W: Weight> GraphBuilder<W> {
+    /// make every edge added with `edge` one way, as with `4 directed` in the text format
+    pub fn directed(mut self) -> GraphBuilder<W> {
+        self.is_directed_by_default = true;
+        return self;
+    }
+
+    pub fn node(mut self, name: &str) -> GraphBuilder<W> {
+        let idx = self.graph_nodes.len();
+        self.graph_nodes.push(GraphNode::new(idx, name.to_string()));
+        return self;
+    }
+
+    pub fn node_with_coordinates(mut self, name: &str, x: f64, y: f64) -> GraphBuilder<W> {
+        let idx = self.graph_nodes.len();
+        self.graph_nodes
+            .push(GraphNode::new_with_coordinates(idx, name.to_string(), x, y));
+        return self;
+    }
+
+    /// an edge that is bidirectional, unless the builder is `directed`
+    pub fn edge(self, from: &str, to: &str, weight: W) -> GraphBuilder<W> {
+        return self.add_edge(from, to, weight, None);
+    }
+
+    /// an edge that can only be travelled from `from` to `to`, as with `A -> B 5`
+    pub fn directed_edge(self, from: &str, to: &str, weight: W) -> GraphBuilder<W> {
+        return self.add_edge(from, to, weight, Some(true));
+    }
+
+    /// an edge that can be travelled both ways, as with `A <-> B 5`
+    pub fn bidirectional_edge(self, from: &str, to: &str, weight: W) -> GraphBuilder<W> {
+        return self.add_edge(from, to, weight, Some(false));
+    }
+
+    fn add_edge(
+        mut self,
+        from: &str,
+        to: &str,
+        weight: W,
+        is_directed: Option<bool>,
+    ) -> GraphBuilder<W> {
+        self.edges
+            .push((from.to_string(), to.to_string(), weight, is_directed));
+        return self;
+    }
+
+    /// edges from a node to itself are dropped, as they are when reading the text format. The
+    /// weights can't be negative.
+    pub fn build(self) -> Result<Graph<W>, DijkstraError> {
+        return self.build_allowing_negative_weights(false);
+    }
+
+    /// as build, but negative weights are allowed, for use with `bellman_ford`
+    pub fn build_with_signed_weights(self) -> Result<Graph<W>, DijkstraError> {
+        return self.build_allowing_negative_weights(true);
+    }
+
+    fn build_allowing_negative_weights(
+        self,
+        allow_negative_weights: bool,
+    ) -> Result<Graph<W>, DijkstraError> {
+        let mut edges = Vec::with_capacity(self.edges.len());
+        for (from, to, weight, is_directed) in &self.edges {
+            let start_index = self.find_node(from)?;
+            let end_index = self.find_node(to)?;
+            if !allow_negative_weights && *weight < W::zero() {
+                return Err(DijkstraError::NegativeEdgeWeight {
+                    from_name: from.to_string(),
+                    to_name: to.to_string(),
+                    weight: weight.to_string(),
+                });
+            }
+            if start_index == end_index {
+                continue;
+            }
+            if is_directed.unwrap_or(self.is_directed_by_default) {
+                edges.push(Edge::new_directed(start_index, end_index, *weight));
+            } else {
+                edges.push(Edge::new(start_index, end_index, *weight));
+            }
+        }
+        return Graph::new_allowing_negative_weights(
+            self.graph_nodes,
+            edges,
+            allow_negative_weights,
+        );
+    }
+
+    fn find_node(&self, node_name: &str) -> Result<usize, DijkstraError> {
+        return get_node_index_from_node_name(node_name, &self.graph_nodes).ok_or_else(|| {
+            DijkstraError::UnknownNodeName {
+                node_name: node_name.to_string(),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_matches_text_format() {
+        let built = GraphBuilder::new()
+            .node("A")
+            .node("B")
+            .node("C")
+            .edge("A", "B", 1)
+            .directed_edge("B", "C", 2)
+            .edge("C", "C", 3)
+            .build()
+            .unwrap();
+        let parsed =
+            Graph::new_from_string("3\nA\nB\nC\n\n3\nA B 1\nB -> C 2\nC C 3\n\nA C").unwrap();
+        assert_eq!(built, parsed);
+    }
+    #[test]
+    fn test_directed_builder() {
+        let built = GraphBuilder::new()
+            .directed()
+            .node_with_coordinates("A", 0.0, 0.0)
+            .node("B")
+            .edge("A", "B", 1)
+            .bidirectional_edge("B", "A", 2)
+            .build()
+            .unwrap();
+        assert_eq!(built.graph_nodes[0].coordinates, Some((0.0, 0.0)));
+        assert_eq!(
+            built.edges,
+            vec![Edge::new_directed(0, 1, 1), Edge::new(1, 0, 2)]
+        );
+    }
+    #[test]
+    fn test_builder_with_unknown_node() {
+        let built = GraphBuilder::new().node("A").edge("A", "B", 1).build();
+        assert_eq!(
+            built,
+            Err(DijkstraError::UnknownNodeName {
+                node_name: "B".to_string()
+            })
+        );
+    }
+    #[test]
+    fn test_builder_rejects_negative_weights() {
+        let builder = GraphBuilder::<i64>::default()
+            .node("A")
+            .node("B")
+            .directed_edge("A", "B", -3);
+        assert_eq!(
+            builder.clone().build(),
+            Err(DijkstraError::NegativeEdgeWeight {
+                from_name: "A".to_string(),
+                to_name: "B".to_string(),
+                weight: "-3".to_string()
+            })
+        );
+        let graph = builder.build_with_signed_weights().unwrap();
+        assert_eq!(graph.edges(), &[Edge::new_directed(0, 1, -3)]);
+    }
+}
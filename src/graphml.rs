@@ -0,0 +1,1091 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::parse_input::{parse_weight_on_line, Edge, GraphNode};
+use crate::weight::Weight;
+
+use quick_xml::escape::{escape, unescape, EscapeError};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// A `<key>` declaration of a GraphML file, which says what the `<data>` with that key holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphMlKey {
+    pub id: String,
+    /// what the key is for: `node`, `edge`, `graph` or `all`
+    pub domain: String,
+    /// the `attr.name`, or the id for keys without one, i.e. yEd's `nodegraphics`
+    pub name: String,
+    /// every attribute of the declaration other than `id` and `for`, i.e. `attr.name`,
+    /// `attr.type` or `yfiles.type`, in the order they were given
+    pub declaration: Vec<(String, String)>,
+    /// the value of `<data>` left out for this key
+    pub default: Option<String>,
+    /// all of the data holds XML elements, i.e. yEd's shapes, rather than text, so it is kept as XML
+    pub is_xml: bool,
+    /// the `<desc>` of the key
+    pub description: Option<String>,
+}
+
+/// What a GraphML file says about a `<graph>`, `<node>` or `<edge>` that isn't its data, and that
+/// the `Graph` has no place for
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphMlElement {
+    /// the `<desc>` of the element
+    pub description: Option<String>,
+    /// the attributes of the element other than the ones read into the graph, i.e. NetworkX's
+    /// `parse.order`, yEd's `yfiles.foldertype` or the `sourceport` of an edge, in the order they
+    /// were given
+    pub xml_attributes: Vec<(String, String)>,
+}
+
+/// A graph read from GraphML, with everything the `Graph` has no place for kept alongside it,
+/// so that writing it back out loses nothing the searches don't use.
+///
+/// The `<data>` of a node with the key named `name` is its `node_name`, or else its id, and
+/// the data named `x` and `y` are its coordinates. The data of an edge named `weight` is its
+/// weight. Any other data is kept by the name of its key: in the node's `attributes`, in
+/// `edge_attributes`, in `graph_attributes` or, for the data of the `<graphml>` element itself,
+/// in `graphml_attributes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphMl<W = usize> {
+    pub graph: Graph<W>,
+    pub keys: Vec<GraphMlKey>,
+    /// the attributes of the `<graphml>` element, i.e. the namespaces
+    pub root_attributes: Vec<(String, String)>,
+    /// the `<desc>` of the `<graphml>` element
+    pub description: Option<String>,
+    /// the data of the `<graphml>` element, i.e. yEd's `<y:Resources>`
+    pub graphml_attributes: BTreeMap<String, String>,
+    pub graph_id: Option<String>,
+    pub graph_attributes: BTreeMap<String, String>,
+    pub graph_element: GraphMlElement,
+    /// the `edgedefault` of the graph, which edges without a `directed` attribute follow
+    pub is_directed_by_default: bool,
+    /// `node_ids[node_idx]` is the id of the node, which the edges refer to
+    pub node_ids: Vec<String>,
+    /// `edge_ids[edge_pos]` is the id of the edge in `graph.edges`, if it has one
+    pub edge_ids: Vec<Option<String>>,
+    /// `edge_attributes[edge_pos]` is the data of the edge in `graph.edges` other than its weight
+    pub edge_attributes: Vec<BTreeMap<String, String>>,
+    /// `node_elements[node_idx]` is the rest of what the file says about the node
+    pub node_elements: Vec<GraphMlElement>,
+    /// `edge_elements[edge_pos]` is the rest of what the file says about the edge in `graph.edges`
+    pub edge_elements: Vec<GraphMlElement>,
+}
+
+impl<W: Weight> GraphMl<W> {
+    /// the graph with nothing else to keep, i.e. to write a graph that wasn't read from
+    /// GraphML. The nodes are given the ids `n0`, `n1`, ... and the edges are bidirectional by
+    /// default.
+    pub fn new(graph: Graph<W>) -> GraphMl<W> {
+        let node_ids = (0..graph.number_of_nodes)
+            .map(|node_idx| format!("n{}", node_idx))
+            .collect();
+        return GraphMl {
+            keys: Vec::new(),
+            root_attributes: vec![(
+                "xmlns".to_string(),
+                "http://graphml.graphdrawing.org/xmlns".to_string(),
+            )],
+            description: None,
+            graphml_attributes: BTreeMap::new(),
+            graph_id: None,
+            graph_attributes: BTreeMap::new(),
+            graph_element: GraphMlElement::default(),
+            is_directed_by_default: false,
+            node_ids,
+            edge_ids: vec![None; graph.edges.len()],
+            edge_attributes: vec![BTreeMap::new(); graph.edges.len()],
+            node_elements: vec![GraphMlElement::default(); graph.number_of_nodes],
+            edge_elements: vec![GraphMlElement::default(); graph.edges.len()],
+            graph,
+        };
+    }
+
+    /// read a GraphML file of a single graph. Ports, hyperedges and nested graphs aren't
+    /// supported. Every edge needs a weight, either in its data or as the default of the
+    /// weight key, and the weights can't be negative. Edges from a node to itself are kept, so
+    /// that they are written back out, as are the `<desc>` and other XML attributes of elements.
+    pub fn new_from_string(contents: &str) -> Result<GraphMl<W>, DijkstraError> {
+        return GraphMl::new_from_string_allowing_negative_weights(contents, false);
+    }
+
+    /// as new_from_string, but negative weights are allowed, for use with `bellman_ford`
+    pub fn new_from_string_with_signed_weights(
+        contents: &str,
+    ) -> Result<GraphMl<W>, DijkstraError> {
+        return GraphMl::new_from_string_allowing_negative_weights(contents, true);
+    }
+
+    fn new_from_string_allowing_negative_weights(
+        contents: &str,
+        allow_negative_weights: bool,
+    ) -> Result<GraphMl<W>, DijkstraError> {
+        let mut elements = read_graphml_elements(contents)?;
+        let Some((graph_id, is_directed_by_default, graph_element)) = elements.graph.take() else {
+            return Err(graphml_error(contents, 0, "there is no <graph> element"));
+        };
+
+        // a key is XML when all of its data has markup in it, otherwise every value is text
+        let is_markup = |raw: &str| raw.contains('<') && cdata_text(raw).is_none();
+        let mut keys = std::mem::take(&mut elements.keys);
+        for key in keys.iter_mut() {
+            let mut values = elements
+                .all_data()
+                .filter(|(key_id, _, _)| *key_id == key.id)
+                .peekable();
+            key.is_xml = values.peek().is_some() && values.all(|(_, raw, _)| is_markup(raw));
+        }
+        let data_value = |key_id: &str, raw: &str, byte: usize| {
+            let key = keys.iter().find(|key| key.id == key_id).ok_or_else(|| {
+                graphml_error(
+                    contents,
+                    byte,
+                    format!("there is no <key> with the id {:?}", key_id),
+                )
+            })?;
+            if key.is_xml {
+                return Ok((key, raw.trim().to_string()));
+            }
+            let value = xml_text(raw).map_err(|e| graphml_error(contents, byte, e))?;
+            return Ok((key, value));
+        };
+
+        let mut graphml_attributes = graphml_defaults(&keys, "graphml");
+        for (key_id, raw, byte) in &elements.root_data {
+            let (key, value) = data_value(key_id, raw, *byte)?;
+            graphml_attributes.insert(key.name.to_string(), value);
+        }
+
+        let mut graph_attributes = graphml_defaults(&keys, "graph");
+        for (key_id, raw, byte) in &elements.graph_data {
+            let (key, value) = data_value(key_id, raw, *byte)?;
+            graph_attributes.insert(key.name.to_string(), value);
+        }
+
+        let mut graph_nodes = Vec::with_capacity(elements.nodes.len());
+        let mut node_indexes = HashMap::new();
+        for (node_idx, node) in elements.nodes.iter().enumerate() {
+            if node_indexes.insert(node.id.as_str(), node_idx).is_some() {
+                let message = format!("the node id {:?} is used more than once", node.id);
+                return Err(graphml_error(contents, node.byte, message));
+            }
+            let mut attributes = graphml_defaults(&keys, "node");
+            for (key_id, raw, byte) in &node.data {
+                let (key, value) = data_value(key_id, raw, *byte)?;
+                attributes.insert(key.name.to_string(), value);
+            }
+            let node_name = match find_graphml_key(&keys, "node", "name") {
+                Some(key) if !key.is_xml => attributes.remove("name"),
+                _ => None,
+            };
+            let mut graph_node =
+                GraphNode::new(node_idx, node_name.unwrap_or_else(|| node.id.to_string()));
+            let coordinates = (attributes.get("x"), attributes.get("y"));
+            if let (Some(x), Some(y)) = coordinates {
+                match (x.parse::<f64>(), y.parse::<f64>()) {
+                    (Ok(x), Ok(y)) if x.is_finite() && y.is_finite() => {
+                        graph_node.coordinates = Some((x, y));
+                        attributes.remove("x");
+                        attributes.remove("y");
+                    }
+                    // kept as they are, so that they aren't lost
+                    _ => {}
+                }
+            }
+            graph_node.attributes = attributes;
+            graph_nodes.push(graph_node);
+        }
+
+        let weight_key = find_graphml_key(&keys, "edge", "weight");
+        let mut edges = Vec::with_capacity(elements.edges.len());
+        let mut edge_attributes = Vec::with_capacity(elements.edges.len());
+        for edge in &elements.edges {
+            let line = line_at(contents, edge.byte);
+            let find_node = |node_id: &str| {
+                return node_indexes.get(node_id).copied().ok_or_else(|| {
+                    DijkstraError::UnknownNode {
+                        line,
+                        text: line_text(contents, line),
+                        node_name: node_id.to_string(),
+                    }
+                });
+            };
+            let start_idx = find_node(&edge.source)?;
+            let end_idx = find_node(&edge.target)?;
+
+            let mut weight = weight_key
+                .and_then(|key| key.default.as_ref())
+                .map(|weight| (weight.to_string(), edge.byte));
+            let mut attributes = graphml_defaults(&keys, "edge");
+            if let Some(weight_key) = weight_key {
+                attributes.remove(&weight_key.name);
+            }
+            for (key_id, raw, byte) in &edge.data {
+                let (key, value) = data_value(key_id, raw, *byte)?;
+                if Some(key) == weight_key {
+                    weight = Some((value, *byte));
+                } else {
+                    attributes.insert(key.name.to_string(), value);
+                }
+            }
+            let Some((weight, byte)) = weight else {
+                return Err(DijkstraError::InvalidEdge {
+                    line,
+                    text: line_text(contents, line),
+                });
+            };
+            let weight_line = line_at(contents, byte);
+            let weight_text = line_text(contents, weight_line);
+            let weight = parse_weight_on_line(
+                weight.trim(),
+                allow_negative_weights,
+                weight_line,
+                &weight_text,
+            )?;
+
+            if edge.is_directed.unwrap_or(is_directed_by_default) {
+                edges.push(Edge::new_directed(start_idx, end_idx, weight));
+            } else {
+                edges.push(Edge::new(start_idx, end_idx, weight));
+            }
+            edge_attributes.push(attributes);
+        }
+
+        let (node_ids, node_elements) = elements
+            .nodes
+            .into_iter()
+            .map(|node| (node.id, node.element))
+            .unzip();
+        let (edge_ids, edge_elements) = elements
+            .edges
+            .into_iter()
+            .map(|edge| (edge.id, edge.element))
+            .unzip();
+        return Ok(GraphMl {
+            graph: Graph::new_allowing_negative_weights(
+                graph_nodes,
+                edges,
+                allow_negative_weights,
+            )?,
+            keys,
+            root_attributes: elements.root_attributes,
+            description: elements.description,
+            graphml_attributes,
+            graph_id,
+            graph_attributes,
+            graph_element,
+            is_directed_by_default,
+            node_ids,
+            edge_ids,
+            edge_attributes,
+            node_elements,
+            edge_elements,
+        });
+    }
+
+    /// write the graph as GraphML, declaring a key for any data that doesn't have one, i.e. for
+    /// the node names of a graph that wasn't read from GraphML
+    pub fn to_graphml(&self) -> String {
+        let graph = &self.graph;
+        let mut keys = self.keys.clone();
+        if graph
+            .graph_nodes
+            .iter()
+            .zip(&self.node_ids)
+            .any(|(node, node_id)| &node.node_name != node_id)
+        {
+            add_graphml_key(&mut keys, "node", "name", "string");
+        }
+        if graph
+            .graph_nodes
+            .iter()
+            .any(|node| node.coordinates.is_some())
+        {
+            add_graphml_key(&mut keys, "node", "x", "double");
+            add_graphml_key(&mut keys, "node", "y", "double");
+        }
+        if !graph.edges.is_empty() {
+            let is_integer = graph
+                .edges
+                .iter()
+                .all(|edge| edge.weight.to_string().parse::<i64>().is_ok());
+            add_graphml_key(
+                &mut keys,
+                "edge",
+                "weight",
+                if is_integer { "long" } else { "double" },
+            );
+        }
+        for name in graph
+            .graph_nodes
+            .iter()
+            .flat_map(|node| node.attributes.keys())
+        {
+            add_graphml_key(&mut keys, "node", name, "string");
+        }
+        for name in self
+            .edge_attributes
+            .iter()
+            .flat_map(|attributes| attributes.keys())
+        {
+            add_graphml_key(&mut keys, "edge", name, "string");
+        }
+        for name in self.graph_attributes.keys() {
+            add_graphml_key(&mut keys, "graph", name, "string");
+        }
+        for name in self.graphml_attributes.keys() {
+            add_graphml_key(&mut keys, "graphml", name, "string");
+        }
+        let data = |domain: &str, name: &str, value: &str| {
+            let key =
+                find_graphml_key(&keys, domain, name).expect("Expected every key to be added");
+            let value = if key.is_xml {
+                value.into()
+            } else {
+                escape(value)
+            };
+            return format!("<data key=\"{}\">{}</data>", escape(key.id.as_str()), value);
+        };
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<graphml{}>\n",
+            xml_attributes(&self.root_attributes)
+        ));
+        if let Some(description) = &self.description {
+            xml.push_str(&format!("  {}\n", desc(description)));
+        }
+        for key in &keys {
+            let mut attributes = vec![
+                ("id".to_string(), key.id.to_string()),
+                ("for".to_string(), key.domain.to_string()),
+            ];
+            attributes.extend(key.declaration.iter().cloned());
+            let mut key_contents = Vec::new();
+            if let Some(description) = &key.description {
+                key_contents.push(desc(description));
+            }
+            if let Some(default) = &key.default {
+                key_contents.push(format!("<default>{}</default>", escape(default.as_str())));
+            }
+            xml.push_str(&xml_element(
+                1,
+                "key",
+                &xml_attributes(&attributes),
+                &key_contents,
+            ));
+        }
+        for (name, value) in &self.graphml_attributes {
+            xml.push_str(&format!("  {}\n", data("graphml", name, value)));
+        }
+
+        let mut graph_attributes = Vec::new();
+        if let Some(graph_id) = &self.graph_id {
+            graph_attributes.push(("id".to_string(), graph_id.to_string()));
+        }
+        let edge_default = if self.is_directed_by_default {
+            "directed"
+        } else {
+            "undirected"
+        };
+        graph_attributes.push(("edgedefault".to_string(), edge_default.to_string()));
+        graph_attributes.extend(self.graph_element.xml_attributes.iter().cloned());
+        xml.push_str(&format!("  <graph{}>\n", xml_attributes(&graph_attributes)));
+        if let Some(description) = &self.graph_element.description {
+            xml.push_str(&format!("    {}\n", desc(description)));
+        }
+        for (name, value) in &self.graph_attributes {
+            xml.push_str(&format!("    {}\n", data("graph", name, value)));
+        }
+
+        for (node_idx, (node, node_id)) in graph.graph_nodes.iter().zip(&self.node_ids).enumerate()
+        {
+            let element = &self.node_elements[node_idx];
+            let mut node_data: Vec<String> = element
+                .description
+                .as_deref()
+                .map(desc)
+                .into_iter()
+                .collect();
+            if find_graphml_key(&keys, "node", "name").is_some() {
+                node_data.push(data("node", "name", &node.node_name));
+            }
+            if let Some((x, y)) = node.coordinates {
+                node_data.push(data("node", "x", &x.to_string()));
+                node_data.push(data("node", "y", &y.to_string()));
+            }
+            for (name, value) in &node.attributes {
+                node_data.push(data("node", name, value));
+            }
+            let mut attributes = vec![("id".to_string(), node_id.to_string())];
+            attributes.extend(element.xml_attributes.iter().cloned());
+            xml.push_str(&xml_element(
+                2,
+                "node",
+                &xml_attributes(&attributes),
+                &node_data,
+            ));
+        }
+
+        for (edge_pos, edge) in graph.edges.iter().enumerate() {
+            let element = &self.edge_elements[edge_pos];
+            let mut attributes = Vec::new();
+            if let Some(edge_id) = &self.edge_ids[edge_pos] {
+                attributes.push(("id".to_string(), edge_id.to_string()));
+            }
+            attributes.push((
+                "source".to_string(),
+                self.node_ids[edge.index_first].to_string(),
+            ));
+            attributes.push((
+                "target".to_string(),
+                self.node_ids[edge.index_second].to_string(),
+            ));
+            if edge.is_directed != self.is_directed_by_default {
+                attributes.push(("directed".to_string(), edge.is_directed.to_string()));
+            }
+            attributes.extend(element.xml_attributes.iter().cloned());
+            let mut edge_data: Vec<String> = element
+                .description
+                .as_deref()
+                .map(desc)
+                .into_iter()
+                .collect();
+            edge_data.push(data("edge", "weight", &edge.weight.to_string()));
+            for (name, value) in &self.edge_attributes[edge_pos] {
+                edge_data.push(data("edge", name, value));
+            }
+            xml.push_str(&xml_element(
+                2,
+                "edge",
+                &xml_attributes(&attributes),
+                &edge_data,
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        return xml;
+    }
+}
+
+impl<W: Weight> Graph<W> {
+    /// read a graph from GraphML, dropping anything the graph has no place for, see
+    /// `GraphMl::new_from_string` to keep it
+    pub fn new_from_graphml(contents: &str) -> Result<Graph<W>, DijkstraError> {
+        return Ok(GraphMl::new_from_string(contents)?.graph);
+    }
+
+    /// the graph as GraphML, see `GraphMl::to_graphml`
+    pub fn to_graphml(&self) -> String {
+        return GraphMl::new(self.clone()).to_graphml();
+    }
+}
+
+/// a `<data>` element: the id of its key, its contents as they are in the file, and where it is
+type GraphMlData = Vec<(String, String, usize)>;
+
+struct GraphMlNode {
+    id: String,
+    element: GraphMlElement,
+    data: GraphMlData,
+    byte: usize,
+}
+
+struct GraphMlEdge {
+    id: Option<String>,
+    source: String,
+    target: String,
+    is_directed: Option<bool>,
+    element: GraphMlElement,
+    data: GraphMlData,
+    byte: usize,
+}
+
+/// the elements of a GraphML file, before the data is matched to its keys
+#[derive(Default)]
+struct GraphMlElements {
+    root_attributes: Vec<(String, String)>,
+    description: Option<String>,
+    root_data: GraphMlData,
+    keys: Vec<GraphMlKey>,
+    /// the id, edgedefault and the rest of the `<graph>`, once it has been found
+    graph: Option<(Option<String>, bool, GraphMlElement)>,
+    graph_data: GraphMlData,
+    nodes: Vec<GraphMlNode>,
+    edges: Vec<GraphMlEdge>,
+}
+
+impl GraphMlElements {
+    fn all_data(&self) -> impl Iterator<Item = &(String, String, usize)> {
+        let node_data = self.nodes.iter().flat_map(|node| &node.data);
+        let edge_data = self.edges.iter().flat_map(|edge| &edge.data);
+        return self
+            .root_data
+            .iter()
+            .chain(&self.graph_data)
+            .chain(node_data)
+            .chain(edge_data);
+    }
+}
+
+/// the element whose children are being read
+enum Within {
+    Root,
+    Key(GraphMlKey),
+    Graph,
+    Node(usize),
+    Edge(usize),
+}
+
+fn read_graphml_elements(contents: &str) -> Result<GraphMlElements, DijkstraError> {
+    let mut reader = Reader::from_str(contents);
+    reader.config_mut().trim_text(true);
+    let mut elements = GraphMlElements::default();
+    let mut within = Within::Root;
+    loop {
+        let byte = reader.buffer_position() as usize;
+        let event = reader
+            .read_event()
+            .map_err(|e| graphml_error(contents, reader.error_position() as usize, e))?;
+        let (element, is_empty) = match event {
+            Event::Start(element) => (element, false),
+            Event::Empty(element) => (element, true),
+            Event::End(_) => {
+                within = match std::mem::replace(&mut within, Within::Root) {
+                    Within::Key(key) => {
+                        elements.keys.push(key);
+                        Within::Root
+                    }
+                    Within::Node(_) | Within::Edge(_) => Within::Graph,
+                    Within::Graph | Within::Root => Within::Root,
+                };
+                continue;
+            }
+            Event::Eof => break,
+            // the declaration, comments and the text between elements
+            _ => continue,
+        };
+
+        let attributes = xml_element_attributes(&element, contents, byte)?;
+        // the attributes of an element that aren't read into the graph
+        let other_attributes = |read: &[&str]| {
+            return attributes
+                .iter()
+                .filter(|(key, _)| !read.contains(&key.as_str()))
+                .cloned()
+                .collect::<Vec<(String, String)>>();
+        };
+        let attribute = |name: &str| {
+            return attributes
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.to_string());
+        };
+        let required_attribute = |name: &str| {
+            return attribute(name).ok_or_else(|| {
+                let message = format!("the element has no {:?} attribute", name);
+                graphml_error(contents, byte, message)
+            });
+        };
+        let contents_of_element = |reader: &mut Reader<&[u8]>| {
+            if is_empty {
+                return Ok(String::new());
+            }
+            let text = reader
+                .read_text(element.name())
+                .map_err(|e| graphml_error(contents, byte, e))?;
+            return Ok(text.into_owned());
+        };
+
+        match (element.name().as_ref(), &mut within) {
+            (b"graphml", Within::Root) => elements.root_attributes = attributes.clone(),
+            (b"key", Within::Root) => {
+                let id = required_attribute("id")?;
+                let key = GraphMlKey {
+                    name: attribute("attr.name").unwrap_or_else(|| id.to_string()),
+                    id,
+                    domain: attribute("for").unwrap_or_else(|| "all".to_string()),
+                    declaration: attributes
+                        .iter()
+                        .filter(|(key, _)| key != "id" && key != "for")
+                        .cloned()
+                        .collect(),
+                    default: None,
+                    is_xml: false,
+                    description: None,
+                };
+                if is_empty {
+                    elements.keys.push(key);
+                } else {
+                    within = Within::Key(key);
+                }
+            }
+            (b"default", Within::Key(_)) => {
+                let default = contents_of_element(&mut reader)?;
+                let default = xml_text(&default).map_err(|e| graphml_error(contents, byte, e))?;
+                if let Within::Key(key) = &mut within {
+                    key.default = Some(default);
+                }
+            }
+            (b"desc", _) => {
+                let description = contents_of_element(&mut reader)?;
+                let description =
+                    xml_text(&description).map_err(|e| graphml_error(contents, byte, e))?;
+                let described = match &mut within {
+                    Within::Root => &mut elements.description,
+                    Within::Key(key) => &mut key.description,
+                    Within::Graph => {
+                        let (_, _, element) = elements
+                            .graph
+                            .as_mut()
+                            .expect("Expected the graph to be read before its contents");
+                        &mut element.description
+                    }
+                    Within::Node(node_idx) => &mut elements.nodes[*node_idx].element.description,
+                    Within::Edge(edge_pos) => &mut elements.edges[*edge_pos].element.description,
+                };
+                *described = Some(description);
+            }
+            (b"graph", Within::Root) if elements.graph.is_none() => {
+                let is_directed_by_default = match attribute("edgedefault").as_deref() {
+                    None | Some("directed") => true,
+                    Some("undirected") => false,
+                    Some(_) => {
+                        return Err(graphml_error(contents, byte, "the edgedefault is invalid"))
+                    }
+                };
+                let element = GraphMlElement {
+                    description: None,
+                    xml_attributes: other_attributes(&["id", "edgedefault"]),
+                };
+                elements.graph = Some((attribute("id"), is_directed_by_default, element));
+                if !is_empty {
+                    within = Within::Graph;
+                }
+            }
+            (b"node", Within::Graph) => {
+                elements.nodes.push(GraphMlNode {
+                    id: required_attribute("id")?,
+                    element: GraphMlElement {
+                        description: None,
+                        xml_attributes: other_attributes(&["id"]),
+                    },
+                    data: Vec::new(),
+                    byte,
+                });
+                if !is_empty {
+                    within = Within::Node(elements.nodes.len() - 1);
+                }
+            }
+            (b"edge", Within::Graph) => {
+                let is_directed = match attribute("directed").as_deref() {
+                    None => None,
+                    Some("true") => Some(true),
+                    Some("false") => Some(false),
+                    Some(_) => {
+                        return Err(graphml_error(
+                            contents,
+                            byte,
+                            "directed is not true or false",
+                        ))
+                    }
+                };
+                elements.edges.push(GraphMlEdge {
+                    id: attribute("id"),
+                    source: required_attribute("source")?,
+                    target: required_attribute("target")?,
+                    is_directed,
+                    element: GraphMlElement {
+                        description: None,
+                        xml_attributes: other_attributes(&["id", "source", "target", "directed"]),
+                    },
+                    data: Vec::new(),
+                    byte,
+                });
+                if !is_empty {
+                    within = Within::Edge(elements.edges.len() - 1);
+                }
+            }
+            (b"data", Within::Root | Within::Graph | Within::Node(_) | Within::Edge(_)) => {
+                let data = (
+                    required_attribute("key")?,
+                    contents_of_element(&mut reader)?,
+                    byte,
+                );
+                match within {
+                    Within::Root => elements.root_data.push(data),
+                    Within::Node(node_idx) => elements.nodes[node_idx].data.push(data),
+                    Within::Edge(edge_pos) => elements.edges[edge_pos].data.push(data),
+                    _ => elements.graph_data.push(data),
+                }
+            }
+            // ports, hyperedges, nested graphs and anything else
+            (name, _) => {
+                let message = format!(
+                    "the <{}> element isn't supported here",
+                    String::from_utf8_lossy(name)
+                );
+                return Err(graphml_error(contents, byte, message));
+            }
+        }
+    }
+    return Ok(elements);
+}
+
+fn xml_element_attributes(
+    element: &BytesStart,
+    contents: &str,
+    byte: usize,
+) -> Result<Vec<(String, String)>, DijkstraError> {
+    let mut attributes = Vec::new();
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|e| graphml_error(contents, byte, e))?;
+        let value = attribute
+            .unescape_value()
+            .map_err(|e| graphml_error(contents, byte, e))?;
+        attributes.push((
+            String::from_utf8_lossy(attribute.key.as_ref()).into_owned(),
+            value.into_owned(),
+        ));
+    }
+    return Ok(attributes);
+}
+
+/// the key with the name for the domain, or for every domain
+fn find_graphml_key<'a>(
+    keys: &'a [GraphMlKey],
+    domain: &str,
+    name: &str,
+) -> Option<&'a GraphMlKey> {
+    return keys
+        .iter()
+        .find(|key| key.name == name && (key.domain == domain || key.domain == "all"));
+}
+
+/// the values of the data left out of the elements of the domain
+fn graphml_defaults(keys: &[GraphMlKey], domain: &str) -> BTreeMap<String, String> {
+    return keys
+        .iter()
+        .filter(|key| key.domain == domain || key.domain == "all")
+        .filter_map(|key| Some((key.name.to_string(), key.default.clone()?)))
+        .collect();
+}
+
+/// declare a key for the data, unless there already is one. Its id is the name, or the domain
+/// and the name if another key has that id.
+fn add_graphml_key(keys: &mut Vec<GraphMlKey>, domain: &str, name: &str, attr_type: &str) {
+    if find_graphml_key(keys, domain, name).is_some() {
+        return;
+    }
+    let id = if keys.iter().any(|key| key.id == name) {
+        format!("{}_{}", domain, name)
+    } else {
+        name.to_string()
+    };
+    keys.push(GraphMlKey {
+        id,
+        domain: domain.to_string(),
+        name: name.to_string(),
+        declaration: vec![
+            ("attr.name".to_string(), name.to_string()),
+            ("attr.type".to_string(), attr_type.to_string()),
+        ],
+        default: None,
+        is_xml: false,
+        description: None,
+    });
+}
+
+/// the text inside an element, which is either escaped or, as yEd writes it, a CDATA section
+fn xml_text(raw: &str) -> Result<String, EscapeError> {
+    if let Some(text) = cdata_text(raw) {
+        return Ok(text.to_string());
+    }
+    return Ok(unescape(raw)?.into_owned());
+}
+
+/// the text of contents that are a single CDATA section
+fn cdata_text(raw: &str) -> Option<&str> {
+    let text = raw.trim().strip_prefix("<![CDATA[")?.strip_suffix("]]>")?;
+    if text.contains("]]>") {
+        return None;
+    }
+    return Some(text);
+}
+
+fn xml_attributes(attributes: &[(String, String)]) -> String {
+    return attributes
+        .iter()
+        .map(|(key, value)| format!(" {}=\"{}\"", key, escape(value.as_str())))
+        .collect();
+}
+
+/// an element nested `depth` elements deep, with its contents on the lines inside it
+fn xml_element(depth: usize, name: &str, attributes: &str, contents: &[String]) -> String {
+    let indent = "  ".repeat(depth);
+    if contents.is_empty() {
+        return format!("{}<{}{}/>\n", indent, name, attributes);
+    }
+    let mut xml = format!("{}<{}{}>\n", indent, name, attributes);
+    for line in contents {
+        xml.push_str(&format!("{}  {}\n", indent, line));
+    }
+    xml.push_str(&format!("{}</{}>\n", indent, name));
+    return xml;
+}
+
+fn desc(description: &str) -> String {
+    return format!("<desc>{}</desc>", escape(description));
+}
+
+/// the line of the first character at or after the byte that isn't whitespace, as the position
+/// before an element can be at the end of the line before it
+fn line_at(contents: &str, byte: usize) -> usize {
+    let byte = byte.min(contents.len());
+    let whitespace = contents[byte..].len() - contents[byte..].trim_start().len();
+    return contents[..byte + whitespace].matches('\n').count() + 1;
+}
+
+fn line_text(contents: &str, line: usize) -> String {
+    return contents.lines().nth(line - 1).unwrap_or("").to_string();
+}
+
+fn graphml_error(contents: &str, byte: usize, message: impl fmt::Display) -> DijkstraError {
+    return DijkstraError::InvalidGraphMl {
+        line: line_at(contents, byte),
+        message: message.to_string(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weight::TotalF64;
+
+    use std::fs;
+
+    #[test]
+    fn read_names_weights_and_attributes() {
+        let contents = fs::read_to_string("src/test/graphml/roads.graphml").unwrap();
+        let graphml = GraphMl::<usize>::new_from_string(&contents).unwrap();
+        let graph = &graphml.graph;
+        let names: Vec<&str> = graph
+            .graph_nodes
+            .iter()
+            .map(|node| node.node_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Cardiff", "Bristol", "London", "depot"]);
+        assert_eq!(graph.graph_nodes[2].coordinates, Some((530.0, 180.0)));
+        assert_eq!(
+            graph.graph_nodes[0]
+                .attributes
+                .get("country")
+                .map(|c| c.as_str()),
+            Some("Wales & the Marches")
+        );
+        assert!(graph.graph_nodes[1].attributes["d4"].starts_with("<y:ShapeNode>"));
+        assert_eq!(
+            graph.edges,
+            vec![
+                Edge::new(0, 1, 44),
+                Edge::new(1, 2, 114),
+                Edge::new_directed(2, 3, 1),
+                Edge::new(3, 3, 1)
+            ]
+        );
+        assert_eq!(
+            graphml.edge_attributes[1].get("road").map(|r| r.as_str()),
+            Some("M4")
+        );
+        assert_eq!(graphml.graph_attributes["source"], "ordnance survey");
+        assert_eq!(graphml.node_ids, vec!["n0", "n1", "n2", "depot"]);
+
+        let mut path_finder =
+            crate::find_path::PathFinder::new(graph.clone(), vec![(0, 3)]).unwrap();
+        assert_eq!(path_finder.dijkstra(), Ok((159, vec![0, 1, 2, 3])));
+    }
+    #[test]
+    fn round_trip_keeps_everything() {
+        let contents = fs::read_to_string("src/test/graphml/roads.graphml").unwrap();
+        let graphml = GraphMl::<usize>::new_from_string(&contents).unwrap();
+        let written = graphml.to_graphml();
+        assert!(written.contains("<y:ShapeNode>"));
+        assert!(written.contains("xmlns:y=\"http://www.yworks.com/xml/graphml\""));
+        assert_eq!(GraphMl::new_from_string(&written), Ok(graphml));
+
+        // a graph that wasn't read from GraphML
+        let contents = fs::read_to_string("src/test/uk.txt").unwrap();
+        let graph = Graph::new_from_string(&contents).unwrap();
+        assert_eq!(Graph::new_from_graphml(&graph.to_graphml()), Ok(graph));
+
+        let mut graph_nodes = vec![
+            GraphNode::new_with_coordinates(0, "<A & B>".to_string(), 0.5, -1.0),
+            GraphNode::new(1, "n0".to_string()),
+        ];
+        graph_nodes[1]
+            .attributes
+            .insert("colour".to_string(), "\"blue\"".to_string());
+        let graph = Graph::new(
+            graph_nodes,
+            vec![Edge::new_directed(0, 1, TotalF64::new(2.5).unwrap())],
+        )
+        .unwrap();
+        let written = graph.to_graphml();
+        assert!(written.contains(
+            "<key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>"
+        ));
+        assert_eq!(Graph::new_from_graphml(&written), Ok(graph));
+    }
+    #[test]
+    fn round_trip_keeps_yed_and_networkx_exports() {
+        let contents = fs::read_to_string("src/test/graphml/yed.graphml").unwrap();
+        let graphml = GraphMl::<usize>::new_from_string(&contents).unwrap();
+        assert_eq!(
+            graphml.graph.edges,
+            vec![Edge::new_directed(0, 1, 120), Edge::new_directed(1, 2, 30)]
+        );
+        assert_eq!(graphml.description.as_deref(), Some("Ferry and rail links"));
+        assert_eq!(graphml.graphml_attributes["d7"], "<y:Resources/>");
+        assert_eq!(
+            graphml.graph_attributes["Description"],
+            "crossings in summer"
+        );
+        assert_eq!(
+            graphml.graph_element,
+            GraphMlElement {
+                description: Some("Holyhead to Dublin".to_string()),
+                xml_attributes: vec![
+                    ("parse.order".to_string(), "nodesfirst".to_string()),
+                    ("parse.nodeids".to_string(), "free".to_string())
+                ]
+            }
+        );
+        assert_eq!(
+            graphml.node_elements[0],
+            GraphMlElement {
+                description: Some("Holyhead port".to_string()),
+                xml_attributes: vec![("yfiles.foldertype".to_string(), "folder".to_string())]
+            }
+        );
+        assert_eq!(
+            graphml.graph.graph_nodes[0].attributes["description"],
+            "ferry terminal & station"
+        );
+        assert_eq!(
+            graphml.edge_elements[0],
+            GraphMlElement {
+                description: Some("fast ferry".to_string()),
+                xml_attributes: vec![("sourceport".to_string(), "p0".to_string())]
+            }
+        );
+        let weight_key = find_graphml_key(&graphml.keys, "edge", "weight").unwrap();
+        assert_eq!(weight_key.description.as_deref(), Some("minutes"));
+        let written = graphml.to_graphml();
+        for kept in [
+            "<y:Resources/>",
+            "parse.order=\"nodesfirst\"",
+            "yfiles.foldertype=\"folder\"",
+            "sourceport=\"p0\"",
+            "<desc>fast ferry</desc>",
+        ] {
+            assert!(written.contains(kept), "{} is missing", kept);
+        }
+        assert_eq!(GraphMl::new_from_string(&written), Ok(graphml));
+
+        let contents = fs::read_to_string("src/test/graphml/networkx.graphml").unwrap();
+        let graphml = GraphMl::<TotalF64>::new_from_string(&contents).unwrap();
+        let glasgow = &graphml.graph.graph_nodes[0];
+        assert_eq!(glasgow.node_name, "Glasgow");
+        assert_eq!(glasgow.coordinates, Some((-4.25, 55.86)));
+        assert_eq!(glasgow.attributes["population"], "635000");
+        assert_eq!(graphml.graph_attributes["name"], "scotland");
+        assert_eq!(
+            graphml.root_attributes[2],
+            (
+                "xsi:schemaLocation".to_string(),
+                "http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd".to_string()
+            )
+        );
+        let mut context = crate::search_context::SearchContext::new(4);
+        assert_eq!(
+            context.dijkstra(&graphml.graph, 0, 3),
+            Ok((TotalF64::new(97.25).unwrap(), vec![0, 2, 3]))
+        );
+        assert_eq!(GraphMl::new_from_string(&graphml.to_graphml()), Ok(graphml));
+    }
+    #[test]
+    fn keys_with_some_text_values_are_text() {
+        let contents = "<graphml>\n<key id=\"d0\" for=\"node\" attr.name=\"note\"/>\n<key id=\"d1\" for=\"node\" yfiles.type=\"nodegraphics\"/>\n<graph edgedefault=\"undirected\">\n<node id=\"a\"><data key=\"d0\">&lt;b&gt;</data><data key=\"d1\"><y:ShapeNode/></data></node>\n<node id=\"b\"><data key=\"d0\">a &amp; b</data><data key=\"d1\"><y:GenericNode/></data></node>\n</graph>\n</graphml>";
+        let graphml = GraphMl::<usize>::new_from_string(contents).unwrap();
+        let note_key = find_graphml_key(&graphml.keys, "node", "note").unwrap();
+        assert!(!note_key.is_xml);
+        let graphics_key = find_graphml_key(&graphml.keys, "node", "d1").unwrap();
+        assert!(graphics_key.is_xml);
+        let graph_nodes = &graphml.graph.graph_nodes;
+        assert_eq!(graph_nodes[0].attributes["note"], "<b>");
+        assert_eq!(graph_nodes[1].attributes["note"], "a & b");
+        assert_eq!(graph_nodes[1].attributes["d1"], "<y:GenericNode/>");
+
+        // one node with markup doesn't stop the text of the others being unescaped
+        let mixed = contents.replace("&lt;b&gt;", "<b/>");
+        let graphml = GraphMl::<usize>::new_from_string(&mixed).unwrap();
+        assert!(
+            !find_graphml_key(&graphml.keys, "node", "note")
+                .unwrap()
+                .is_xml
+        );
+        assert_eq!(graphml.graph.graph_nodes[0].attributes["note"], "<b/>");
+        assert_eq!(graphml.graph.graph_nodes[1].attributes["note"], "a & b");
+        let written = graphml.to_graphml();
+        assert!(written.contains("<data key=\"d0\">a &amp; b</data>"));
+        assert!(written.contains("<y:GenericNode/>"));
+        assert_eq!(GraphMl::new_from_string(&written), Ok(graphml));
+    }
+    #[test]
+    fn invalid_graphml_is_an_error() {
+        let graphml = |body: &str| {
+            let contents = format!(
+                "<graphml>\n<key id=\"w\" for=\"edge\" attr.name=\"weight\"/>\n<graph edgedefault=\"undirected\">\n<node id=\"a\"/>\n<node id=\"b\"/>\n{}\n</graph>\n</graphml>",
+                body
+            );
+            return Graph::<usize>::new_from_graphml(&contents);
+        };
+        assert!(graphml("<edge source=\"a\" target=\"b\"><data key=\"w\">3</data></edge>").is_ok());
+        assert_eq!(
+            graphml("<edge source=\"a\" target=\"c\"><data key=\"w\">3</data></edge>"),
+            Err(DijkstraError::UnknownNode {
+                line: 6,
+                text: "<edge source=\"a\" target=\"c\"><data key=\"w\">3</data></edge>".to_string(),
+                node_name: "c".to_string()
+            })
+        );
+        assert_eq!(
+            graphml("<edge source=\"a\" target=\"b\"/>"),
+            Err(DijkstraError::InvalidEdge {
+                line: 6,
+                text: "<edge source=\"a\" target=\"b\"/>".to_string()
+            })
+        );
+        assert_eq!(
+            graphml("<edge source=\"a\" target=\"b\">\n<data key=\"w\">-3</data>\n</edge>"),
+            Err(DijkstraError::NegativeWeight {
+                line: 7,
+                text: "<data key=\"w\">-3</data>".to_string(),
+                weight: "-3".to_string()
+            })
+        );
+        assert_eq!(
+            graphml("<hyperedge><endpoint node=\"a\"/></hyperedge>"),
+            Err(DijkstraError::InvalidGraphMl {
+                line: 6,
+                message: "the <hyperedge> element isn't supported here".to_string()
+            })
+        );
+        assert!(matches!(
+            graphml("<node id=\"c\">"),
+            Err(DijkstraError::InvalidGraphMl { .. })
+        ));
+    }
+}
@@ -0,0 +1,73 @@
+use crate::parse_input::GraphNode;
+use crate::weight::Weight;
+
+/// An estimate of the remaining distance from a node to the end of the route, used to guide A*.
+/// The estimate must be admissible, i.e. never larger than the true shortest distance,
+/// otherwise A* is not guaranteed to return the shortest route.
+pub trait Heuristic<W = usize> {
+    fn estimate(&self, node_idx: usize, end_idx: usize) -> W;
+}
+
+/// any closure over node indices can be used as a heuristic, i.e. `|node_idx, end_idx| 0`
+impl<F, W> Heuristic<W> for F
+where
+    F: Fn(usize, usize) -> W,
+{
+    fn estimate(&self, node_idx: usize, end_idx: usize) -> W {
+        return self(node_idx, end_idx);
+    }
+}
+
+/// Euclidean distance between the coordinates of two graph nodes.
+/// This is only admissible when the edge weights are at least the straight-line distance
+/// between their nodes, i.e. coordinates and weights are in the same units.
+/// If either node has no coordinates, the estimate falls back to 0.
+pub struct StraightLineHeuristic {
+    coordinates: Vec<Option<(f64, f64)>>,
+}
+
+impl StraightLineHeuristic {
+    pub fn new(graph_nodes: &[GraphNode]) -> StraightLineHeuristic {
+        return StraightLineHeuristic {
+            coordinates: graph_nodes.iter().map(|node| node.coordinates).collect(),
+        };
+    }
+}
+
+impl<W: Weight> Heuristic<W> for StraightLineHeuristic {
+    fn estimate(&self, node_idx: usize, end_idx: usize) -> W {
+        match (self.coordinates[node_idx], self.coordinates[end_idx]) {
+            (Some((x1, y1)), Some((x2, y2))) => {
+                // round down so that the estimate never exceeds an integer edge weight
+                return W::from_f64_rounded_down((x2 - x1).hypot(y2 - y1));
+            }
+            _ => return W::zero(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weight::TotalF64;
+
+    #[test]
+    fn test_straight_line_estimate() {
+        let heuristic = StraightLineHeuristic::new(&[
+            GraphNode::new_with_coordinates(0, "A".to_string(), 0.0, 0.0),
+            GraphNode::new_with_coordinates(1, "B".to_string(), 3.0, 4.0),
+            GraphNode::new_with_coordinates(2, "C".to_string(), 1.0, 1.0),
+            GraphNode::new(3, "D".to_string()),
+        ]);
+        let estimate = |node_idx, end_idx| -> usize { heuristic.estimate(node_idx, end_idx) };
+        assert_eq!(estimate(0, 1), 5);
+        assert_eq!(estimate(1, 0), 5);
+        // sqrt(2) is rounded down for integer weights, but not for decimal ones
+        assert_eq!(estimate(0, 2), 1);
+        assert_eq!(
+            Heuristic::<TotalF64>::estimate(&heuristic, 0, 2),
+            TotalF64::new(2.0_f64.sqrt()).unwrap()
+        );
+        assert_eq!(estimate(0, 3), 0);
+    }
+}
@@ -0,0 +1,380 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::find_path::{Algorithm, PathFinder, RouteBatch};
+use crate::parse_input::{Edge, GraphNode, RouteQuery};
+use crate::route::RouteOutcome;
+use crate::weight::Weight;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+/// The JSON form of a graph, and of the routes to find in it:
+///
+/// ```json
+/// {
+///   "nodes": [
+///     {"name": "London", "coordinates": [530, 180], "attributes": {"country": "England"}},
+///     {"name": "York"}
+///   ],
+///   "edges": [
+///     {"from": 0, "to": 1, "weight": 194, "directed": false}
+///   ],
+///   "routes": [
+///     {"from": 0, "to": 1, "k": 2}
+///   ]
+/// }
+/// ```
+///
+/// Nodes are referred to by their position in `nodes`, counting from 0. `coordinates` and
+/// `attributes` are optional. Attribute values can be any JSON, i.e. `{"population": 9000000}`,
+/// and are kept as strings, with anything other than a string kept as its JSON. `directed` defaults to false.
+/// `routes` is optional, and `k` asks for that many of the shortest routes, as in `A B 2`.
+/// As in the text format, edges from a node to itself are dropped, and a route from a node to
+/// itself is an error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct JsonGraph<W> {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<Edge<W>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<RouteQuery>,
+}
+
+impl<W: Weight> From<Graph<W>> for JsonGraph<W> {
+    fn from(graph: Graph<W>) -> JsonGraph<W> {
+        return JsonGraph {
+            nodes: graph.graph_nodes,
+            edges: graph.edges,
+            routes: Vec::new(),
+        };
+    }
+}
+
+impl<W: Weight> TryFrom<JsonGraph<W>> for Graph<W> {
+    type Error = DijkstraError;
+
+    /// the routes are dropped, and the nodes are numbered by their position in the list. The
+    /// weights can't be negative.
+    fn try_from(json: JsonGraph<W>) -> Result<Graph<W>, DijkstraError> {
+        return graph_from_json(json, false);
+    }
+}
+
+/// node attributes of any JSON type, as strings
+pub(crate) fn attributes_from_json<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, String>, D::Error> {
+    let attributes = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+    return Ok(attributes
+        .into_iter()
+        .map(|(name, value)| match value {
+            serde_json::Value::String(value) => (name, value),
+            value => (name, value.to_string()),
+        })
+        .collect());
+}
+
+/// the graph of the JSON form, see `TryFrom<JsonGraph<W>>`
+fn graph_from_json<W: Weight>(
+    json: JsonGraph<W>,
+    allow_negative_weights: bool,
+) -> Result<Graph<W>, DijkstraError> {
+    let mut graph_nodes = json.nodes;
+    for (idx, node) in graph_nodes.iter_mut().enumerate() {
+        node.index = idx;
+    }
+    for (edge_pos, edge) in json.edges.iter().enumerate() {
+        for node_idx in [edge.index_first, edge.index_second] {
+            if node_idx >= graph_nodes.len() {
+                return Err(DijkstraError::InvalidJson {
+                    message: format!(
+                        "edge {} refers to node {}, which is not in the node list",
+                        edge_pos, node_idx
+                    ),
+                });
+            }
+        }
+    }
+    // as in the text format, an edge from a node to itself is never useful
+    let edges = json
+        .edges
+        .into_iter()
+        .filter(|edge| edge.index_first != edge.index_second)
+        .collect();
+    return Graph::new_allowing_negative_weights(graph_nodes, edges, allow_negative_weights);
+}
+
+impl<W: Weight + Serialize + DeserializeOwned> Graph<W> {
+    /// read a graph from JSON, see the schema in `src/json.rs`. Any routes are ignored, and the
+    /// weights can't be negative.
+    pub fn new_from_json(contents: &str) -> Result<Graph<W>, DijkstraError> {
+        let (graph, _) = parse_json(contents, false)?;
+        return Ok(graph);
+    }
+
+    /// as new_from_json, but negative weights are allowed, for use with `bellman_ford`
+    pub fn new_from_json_with_signed_weights(contents: &str) -> Result<Graph<W>, DijkstraError> {
+        let (graph, _) = parse_json(contents, true)?;
+        return Ok(graph);
+    }
+
+    /// the graph as JSON, with the edges as they were given rather than the connections
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string_pretty(&JsonGraph::from(self.clone()))
+            .expect("Expected a graph to always be representable as JSON");
+    }
+}
+
+impl<W: Weight + Serialize + DeserializeOwned> PathFinder<W> {
+    /// create the PathFinder from JSON with routes, see `Graph::new_from_json`. The number of
+    /// routes to find, `k`, is dropped.
+    pub fn new_from_json(contents: &str) -> Result<PathFinder<W>, DijkstraError> {
+        return PathFinder::new_from_json_allowing_negative_weights(contents, false);
+    }
+
+    /// as new_from_json, but negative weights are allowed, for use with `bellman_ford`
+    pub fn new_from_json_with_signed_weights(
+        contents: &str,
+    ) -> Result<PathFinder<W>, DijkstraError> {
+        return PathFinder::new_from_json_allowing_negative_weights(contents, true);
+    }
+
+    fn new_from_json_allowing_negative_weights(
+        contents: &str,
+        allow_negative_weights: bool,
+    ) -> Result<PathFinder<W>, DijkstraError> {
+        let (graph, route_queries) = parse_json(contents, allow_negative_weights)?;
+        let routes_to_find = route_queries
+            .into_iter()
+            .map(|query| query.map(|query| (query.start_idx, query.end_idx)))
+            .collect::<Result<Vec<(usize, usize)>, DijkstraError>>()?;
+        return PathFinder::new(graph, routes_to_find);
+    }
+
+    /// the graph and the routes of JSON input, as `read_batch_from_string`
+    pub fn read_batch_from_json(contents: &str) -> Result<RouteBatch<W>, DijkstraError> {
+        return parse_json(contents, false);
+    }
+
+    /// as read_batch_from_json, but negative weights are allowed, for use with `bellman_ford`
+    pub fn read_batch_from_json_with_signed_weights(
+        contents: &str,
+    ) -> Result<RouteBatch<W>, DijkstraError> {
+        return parse_json(contents, true);
+    }
+
+    /// batch mode for JSON input, as `solve_batch_from_string_using`. The outcomes can be
+    /// written out as JSON with serde, i.e. `serde_json::to_string(&outcomes)`.
+    pub fn solve_batch_from_json_using(
+        contents: &str,
+        algorithm: Algorithm<W>,
+    ) -> Result<Vec<RouteOutcome<W>>, DijkstraError> {
+        let allow_negative_weights = matches!(algorithm, Algorithm::BellmanFord);
+        let (graph, route_queries) = parse_json(contents, allow_negative_weights)?;
+        return Ok(PathFinder::solve_batch(graph, route_queries, algorithm));
+    }
+}
+
+/// the graph and the routes to find
+fn parse_json<W: Weight + DeserializeOwned>(
+    contents: &str,
+    allow_negative_weights: bool,
+) -> Result<RouteBatch<W>, DijkstraError> {
+    let json: JsonGraph<W> =
+        serde_json::from_str(contents).map_err(|e| DijkstraError::InvalidJson {
+            message: e.to_string(),
+        })?;
+    if !allow_negative_weights {
+        if let Some(edge_pos) = json.edges.iter().position(|edge| edge.weight < W::zero()) {
+            return Err(DijkstraError::InvalidJson {
+                message: format!(
+                    "edge {} has a negative weight {}",
+                    edge_pos, json.edges[edge_pos].weight
+                ),
+            });
+        }
+    }
+    let routes = json.routes.clone();
+    let graph = graph_from_json(json, allow_negative_weights)?;
+    let route_queries = routes
+        .into_iter()
+        .enumerate()
+        .map(|(route_pos, query)| {
+            if query.start_idx == query.end_idx {
+                return Err(DijkstraError::SelfReferentialRoute {
+                    line: route_pos + 1,
+                    text: serde_json::to_string(&query)
+                        .expect("Expected a route to always be representable as JSON"),
+                    node_name: graph.graph_nodes.get(query.start_idx).map_or_else(
+                        || query.start_idx.to_string(),
+                        |node| node.node_name.clone(),
+                    ),
+                });
+            }
+            for node_idx in [query.start_idx, query.end_idx] {
+                if node_idx >= graph.number_of_nodes {
+                    return Err(DijkstraError::InvalidJson {
+                        message: format!(
+                            "route {} refers to node {}, which is not in the node list",
+                            route_pos, node_idx
+                        ),
+                    });
+                }
+            }
+            return Ok(query);
+        })
+        .collect();
+    return Ok((graph, route_queries));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weight::TotalF64;
+
+    use std::fs;
+
+    #[test]
+    fn read_nodes_edges_and_routes() {
+        let contents = fs::read_to_string("src/test/uk.json").unwrap();
+        let graph = Graph::<usize>::new_from_json(&contents).unwrap();
+        let text = Graph::new_from_string(&fs::read_to_string("src/test/uk.txt").unwrap()).unwrap();
+        assert_eq!(graph.connections, text.connections);
+        assert_eq!(
+            graph.graph_nodes[7].node_name,
+            text.graph_nodes[7].node_name
+        );
+        assert_eq!(graph.graph_nodes[7].index, 7);
+        assert_eq!(
+            graph.graph_nodes[9]
+                .attributes
+                .get("country")
+                .map(|c| c.as_str()),
+            Some("England")
+        );
+
+        let path_finder = PathFinder::<usize>::new_from_json(&contents).unwrap();
+        assert_eq!(path_finder.routes_to_find, vec![(1, 2), (7, 0), (9, 3)]);
+    }
+    #[test]
+    fn write_and_read_back() {
+        let contents = "{\"nodes\": [{\"name\": \"A\", \"coordinates\": [0.5, 1], \"attributes\": {\"kind\": \"depot\"}}, {\"name\": \"B\"}], \"edges\": [{\"from\": 0, \"to\": 1, \"weight\": 2.5, \"directed\": true}]}";
+        let graph = Graph::<TotalF64>::new_from_json(contents).unwrap();
+        assert_eq!(
+            graph.edges,
+            vec![Edge::new_directed(0, 1, TotalF64::new(2.5).unwrap())]
+        );
+        assert_eq!(graph.graph_nodes[0].coordinates, Some((0.5, 1.0)));
+        assert_eq!(Graph::new_from_json(&graph.to_json()), Ok(graph));
+
+        // the same graph can be serialised with serde directly
+        let graph = Graph::new_from_string("2\nA\nB\n\n1\nA B 3\n\nA B").unwrap();
+        let json = serde_json::to_string(&graph).unwrap();
+        assert_eq!(
+            json,
+            "{\"nodes\":[{\"name\":\"A\"},{\"name\":\"B\"}],\"edges\":[{\"from\":0,\"to\":1,\"weight\":3,\"directed\":false}]}"
+        );
+        assert_eq!(serde_json::from_str::<Graph>(&json).unwrap(), graph);
+    }
+    #[test]
+    fn solved_routes_as_json() {
+        let contents = fs::read_to_string("src/test/uk.json").unwrap();
+        let mut contents: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        contents["routes"] =
+            serde_json::json!([{"from": 1, "to": 2, "k": 2}, {"from": 0, "to": 42}]);
+        let outcomes = PathFinder::<usize>::solve_batch_from_json_using(
+            &contents.to_string(),
+            Algorithm::Dijkstra,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&outcomes).unwrap(),
+            serde_json::json!([
+                {"routes": [
+                    {"start_idx": 1, "end_idx": 2, "nodes": [1, 2], "node_names": ["Glasgow", "Edinburgh"], "leg_weights": [45], "total_distance": 45},
+                    {"start_idx": 1, "end_idx": 2, "nodes": [1, 3, 2], "node_names": ["Glasgow", "Newcastle", "Edinburgh"], "leg_weights": [145, 107], "total_distance": 252}
+                ]},
+                {"error": "Invalid JSON input: route 1 refers to node 42, which is not in the node list"}
+            ])
+        );
+    }
+    #[test]
+    fn attributes_can_be_any_json() {
+        let contents = "{\"nodes\": [{\"name\": \"London\", \"attributes\": {\"population\": 9000000, \"toll\": true, \"country\": \"England\", \"roads\": [\"M1\", \"M4\"]}}], \"edges\": []}";
+        let graph = Graph::<usize>::new_from_json(contents).unwrap();
+        let attributes: Vec<(&str, &str)> = graph.graph_nodes[0]
+            .attributes
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("country", "England"),
+                ("population", "9000000"),
+                ("roads", "[\"M1\",\"M4\"]"),
+                ("toll", "true")
+            ]
+        );
+        assert_eq!(Graph::new_from_json(&graph.to_json()), Ok(graph));
+    }
+    #[test]
+    fn invalid_json_is_an_error() {
+        let read = |contents: &str| Graph::<usize>::new_from_json(contents);
+        assert!(matches!(
+            read("{\"nodes\": []"),
+            Err(DijkstraError::InvalidJson { .. })
+        ));
+        assert!(matches!(
+            read("{\"nodes\": [{\"name\": \"A\"}], \"edges\": [{\"from\": 0, \"to\": 1, \"weight\": 1}]}"),
+            Err(DijkstraError::InvalidJson { .. })
+        ));
+        let negative = "{\"nodes\": [{\"name\": \"A\"}, {\"name\": \"B\"}], \"edges\": [{\"from\": 0, \"to\": 1, \"weight\": -1, \"directed\": true}]}";
+        assert_eq!(
+            Graph::<i64>::new_from_json(negative),
+            Err(DijkstraError::InvalidJson {
+                message: "edge 0 has a negative weight -1".to_string()
+            })
+        );
+        assert!(Graph::<i64>::new_from_json_with_signed_weights(negative).is_ok());
+        // serde can't be told to allow negative weights, so it never does
+        let error = serde_json::from_str::<Graph<i64>>(negative).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            DijkstraError::NegativeEdgeWeight {
+                from_name: "A".to_string(),
+                to_name: "B".to_string(),
+                weight: "-1".to_string()
+            }
+            .to_string()
+        );
+    }
+    #[test]
+    fn self_loops_and_routes_match_the_text_format() {
+        let contents = "{\"nodes\": [{\"name\": \"A\"}, {\"name\": \"B\"}], \"edges\": [{\"from\": 0, \"to\": 1, \"weight\": 2}, {\"from\": 1, \"to\": 1, \"weight\": 1}], \"routes\": [{\"from\": 0, \"to\": 1}, {\"from\": 1, \"to\": 1}]}";
+        let graph = Graph::<usize>::new_from_json(contents).unwrap();
+        let text = Graph::new_from_string("2\nA\nB\n\n2\nA B 2\nB B 1\n\nA B").unwrap();
+        assert_eq!(graph.edges, text.edges);
+        assert_eq!(graph.connections, text.connections);
+
+        let self_referential = DijkstraError::SelfReferentialRoute {
+            line: 2,
+            text: "{\"from\":1,\"to\":1}".to_string(),
+            node_name: "B".to_string(),
+        };
+        assert_eq!(
+            PathFinder::<usize>::new_from_json(contents).err(),
+            Some(self_referential)
+        );
+        let outcomes =
+            PathFinder::<usize>::solve_batch_from_json_using(contents, Algorithm::Dijkstra)
+                .unwrap();
+        assert_eq!(
+            outcomes[1],
+            RouteOutcome::SelfRoute {
+                line: 2,
+                node_name: "B".to_string()
+            }
+        );
+    }
+}
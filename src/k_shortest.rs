@@ -0,0 +1,154 @@
+use crate::all_pairs::has_negative_weights;
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::parse_input::Edge;
+use crate::search_context::SearchContext;
+use crate::weight::Weight;
+
+impl<W: Weight> SearchContext<W> {
+    /// Yen's algorithm: up to k loopless routes from start to end, shortest first. Each route after
+    /// the first branches off one of the routes already found (the root), and the rest of it (the
+    /// spur) is found with dijkstra while avoiding the edges the earlier routes took after the same
+    /// root and the nodes of the root, so that no route repeats a node.
+    /// If any of the weights are negative, the routes are found with bellman_ford instead.
+    pub fn k_shortest_paths(
+        &mut self,
+        graph: &Graph<W>,
+        start_idx: usize,
+        end_idx: usize,
+        k: usize,
+    ) -> Result<Vec<(W, Vec<usize>)>, DijkstraError> {
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let has_negative_weights = has_negative_weights(graph);
+        let first = if has_negative_weights {
+            self.bellman_ford(graph, start_idx, end_idx)?
+        } else {
+            self.dijkstra(graph, start_idx, end_idx)?
+        };
+        let mut found = vec![first];
+        // routes that branch off the routes found so far, that might be the next shortest
+        let mut candidates: Vec<(W, Vec<usize>)> = Vec::new();
+
+        while found.len() < k {
+            let (_, previous) = &found[found.len() - 1];
+            for spur_pos in 0..previous.len() - 1 {
+                let root = &previous[..=spur_pos];
+                let spur_idx = previous[spur_pos];
+                let mut is_blocked_node = vec![false; graph.number_of_nodes];
+                for &node_idx in &root[..spur_pos] {
+                    is_blocked_node[node_idx] = true;
+                }
+                let blocked_edges: Vec<(usize, usize)> = found
+                    .iter()
+                    .filter(|(_, nodes)| nodes.len() > spur_pos + 1 && &nodes[..=spur_pos] == root)
+                    .map(|(_, nodes)| (spur_idx, nodes[spur_pos + 1]))
+                    .collect();
+                let is_usable = |edge: &Edge<W>| {
+                    return !is_blocked_node[edge.index_second]
+                        && !blocked_edges.contains(&(edge.index_first, edge.index_second));
+                };
+
+                let spur = if has_negative_weights {
+                    self.bellman_ford_along(graph, spur_idx, end_idx, &is_usable)
+                } else {
+                    self.a_star_along(graph, spur_idx, end_idx, &|_, _| W::zero(), &is_usable)
+                };
+                let spur = match spur {
+                    Ok(spur) => spur,
+                    Err(DijkstraError::Disconnected { .. })
+                    | Err(DijkstraError::DistanceOverflow { .. }) => continue,
+                    Err(e) => return Err(e),
+                };
+                let root_dist = match path_distance(graph, root) {
+                    Some(dist) => dist,
+                    None => continue,
+                };
+                let (spur_dist, spur_nodes) = spur;
+                let dist = match root_dist.checked_add(spur_dist) {
+                    Some(dist) => dist,
+                    None => continue,
+                };
+                let mut nodes = root[..spur_pos].to_vec();
+                nodes.extend(spur_nodes);
+                let is_new = !found.iter().any(|(_, found_nodes)| *found_nodes == nodes)
+                    && !candidates
+                        .iter()
+                        .any(|(_, candidate_nodes)| *candidate_nodes == nodes);
+                if is_new {
+                    candidates.push((dist, nodes));
+                }
+            }
+
+            // the shortest candidate, with ties broken by the nodes so the order is repeatable
+            let best_pos =
+                match (0..candidates.len()).min_by(|&a, &b| candidates[a].cmp(&candidates[b])) {
+                    Some(pos) => pos,
+                    None => break,
+                };
+            found.push(candidates.swap_remove(best_pos));
+        }
+        return Ok(found);
+    }
+}
+
+/// the distance along consecutive nodes, or None if it overflows
+fn path_distance<W: Weight>(graph: &Graph<W>, nodes: &[usize]) -> Option<W> {
+    return nodes.windows(2).try_fold(W::zero(), |total, leg| {
+        let edge = graph.connections[leg[0]]
+            .iter()
+            .find(|edge| edge.index_second == leg[1])
+            .expect("Expected an edge between consecutive nodes of a route");
+        return total.checked_add(edge.weight);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the example graph from Yen's algorithm on Wikipedia
+    const YEN_EXAMPLE: &str = "6\nC\nD\nE\nF\nG\nH\n\n9 directed\nC D 3\nC E 2\nD F 4\nE D 1\nE F 2\nE G 3\nF G 2\nF H 1\nG H 2\n\nC H";
+
+    #[test]
+    fn finds_the_three_shortest_paths() {
+        let graph = Graph::new_from_string(YEN_EXAMPLE).unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let paths = context.k_shortest_paths(&graph, 0, 5, 3).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                (5, vec![0, 2, 3, 5]),
+                (7, vec![0, 2, 4, 5]),
+                (8, vec![0, 1, 3, 5]),
+            ]
+        );
+    }
+    #[test]
+    fn paths_are_loopless_and_in_order() {
+        let graph = Graph::new_from_string(YEN_EXAMPLE).unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        // there are only so many loopless paths from C to H
+        let paths = context.k_shortest_paths(&graph, 0, 5, 100).unwrap();
+        assert_eq!(paths.len(), 7);
+        for (dist, nodes) in &paths {
+            assert_eq!(path_distance(&graph, nodes), Some(*dist));
+            let mut sorted = nodes.clone();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(sorted.len(), nodes.len());
+        }
+        assert!(paths.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        assert_eq!(context.k_shortest_paths(&graph, 0, 5, 0), Ok(Vec::new()));
+        assert_eq!(
+            context.k_shortest_paths(&graph, 5, 0, 2),
+            Err(DijkstraError::Disconnected {
+                start_idx: 5,
+                end_idx: 0,
+                start_name: "H".to_string(),
+                end_name: "C".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Shortest routes through a weighted graph, using Dijkstra's algorithm or A*.
+//!
+//! ```
+//! use rust_dijkstra::{GraphBuilder, SearchContext};
+//!
+//! let graph = GraphBuilder::new()
+//!     .node("Cardiff")
+//!     .node("Bristol")
+//!     .node("London")
+//!     .edge("Cardiff", "Bristol", 44)
+//!     .edge("Bristol", "London", 114)
+//!     .build()
+//!     .unwrap();
+//! let mut context = SearchContext::new(graph.number_of_nodes());
+//! let (dist, nodes_in_order) = context.dijkstra(&graph, 0, 2).unwrap();
+//! assert_eq!(dist, 158);
+//! assert_eq!(nodes_in_order, vec![0, 1, 2]);
+//! ```
+//!
+//! ## Stability
+//!
+//! Stable, and only changed with a new major version:
+//! - `Graph` and its accessors. Its fields are private, as `connections` and
+//!   `reverse_connections` are derived from `edges` when the graph is constructed.
+//! - `GraphBuilder`, `GraphNode`, `Edge` and `RouteQuery`, including their public fields
+//! - `PathFinder`, `SearchContext`, `Algorithm` and `RouteBatch`, and their query functions
+//! - `Route` and `RouteOutcome`
+//! - `DijkstraError`. New variants may be added, so matches need a wildcard arm.
+//! - the `Heuristic` and `Weight` traits, and `TotalF64`
+//!
+//! Unstable, and may change in any release:
+//! - `StraightLineHeuristic`, i.e. how its estimate is rounded
+//! - the text of `Display` implementations, including error messages
+#![allow(clippy::needless_return)]
+
+mod all_pairs;
+mod bellman_ford;
+mod bfs;
+mod bidirectional;
+mod components;
+mod construct_graph;
+mod csv_input;
+mod dimacs;
+mod dot;
+mod error;
+mod find_path;
+mod graph_builder;
+mod graphml;
+mod heuristic;
+mod json;
+mod k_shortest;
+mod nearest_source;
+mod parse_input;
+#[cfg(test)]
+mod random_graph;
+mod route;
+mod search_context;
+mod shortest_path_tree;
+mod spanning_tree;
+mod weight;
+
+pub use crate::all_pairs::AllPairs;
+pub use crate::bfs::WeightProfile;
+pub use crate::construct_graph::Graph;
+pub use crate::csv_input::CsvColumns;
+pub use crate::dimacs::DimacsQueries;
+pub use crate::error::DijkstraError;
+pub use crate::find_path::{Algorithm, PathFinder, RouteBatch};
+pub use crate::graph_builder::GraphBuilder;
+pub use crate::graphml::{GraphMl, GraphMlElement, GraphMlKey};
+pub use crate::heuristic::{Heuristic, StraightLineHeuristic};
+pub use crate::nearest_source::NearestSources;
+pub use crate::parse_input::{Edge, GraphNode, RouteQuery};
+pub use crate::route::{Route, RouteOutcome};
+pub use crate::search_context::SearchContext;
+pub use crate::shortest_path_tree::ShortestPathTree;
+pub use crate::spanning_tree::SpanningForest;
+pub use crate::weight::{TotalF64, Weight, WeightParseError};
@@ -1,24 +1,113 @@
-mod construct_graph;
-mod find_path;
-mod parse_input;
+#![allow(clippy::needless_return)]
 
-use crate::find_path::PathFinder;
+use rust_dijkstra::{
+    Algorithm, AllPairs, Graph, PathFinder, RouteBatch, RouteOutcome, SearchContext,
+    StraightLineHeuristic, TotalF64, Weight,
+};
 
-use std::{env, fs};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::{env, error::Error, fs};
 
-fn main() -> Result<(), String> {
+const USAGE: &str =
+    "Please provide relative file path as input arg, i.e. `$ cargo run <src/test/uk.txt> [--a-star | --bidirectional | --bellman-ford] [--strict] [--decimal-weights] [--all-pairs | --tree | --components | --dot] [--format=text | --format=json]`";
+
+/// which search is used to find the routes
+#[derive(PartialEq)]
+enum Search {
+    Dijkstra,
+    /// A* with the straight-line heuristic
+    AStar,
+    Bidirectional,
+    /// allows negative weights
+    BellmanFord,
+}
+
+/// what is printed, instead of the routes
+#[derive(PartialEq)]
+enum Report {
+    Routes,
+    /// the distance between every pair of nodes as CSV
+    AllPairs,
+    /// the shortest path tree from the start of each route
+    Tree,
+    /// the nodes of each (strongly) connected component
+    Components,
+    /// the graph in Graphviz's DOT language, with the routes highlighted
+    Dot,
+}
+
+/// how the input is written, and so how the routes are printed
+#[derive(PartialEq)]
+enum Format {
+    /// the text format of `src/test/uk.txt`
+    Text,
+    /// the JSON schema of `src/json.rs`, used by default for `.json` files
+    Json,
+}
+
+/// command line options, i.e. `$ cargo run <src/test/uk.txt> --a-star`
+struct Options {
+    filename: String,
+    search: Search,
+    /// stop at the first route that can't be found, instead of reporting an outcome for every route
+    strict: bool,
+    /// read the edge weights as decimals, i.e. `A B 2.5`, rather than integers
+    decimal_weights: bool,
+    report: Report,
+    format: Format,
+}
+
+impl Options {
+    fn new_from_args(args: &[String]) -> Result<Options, String> {
+        let mut filename = None;
+        let mut search = None;
+        let mut strict = false;
+        let mut decimal_weights = false;
+        let mut report = None;
+        let mut format = None;
+        for arg in args {
+            match arg.as_str() {
+                "--a-star" if search.is_none() => search = Some(Search::AStar),
+                "--bidirectional" if search.is_none() => search = Some(Search::Bidirectional),
+                "--bellman-ford" if search.is_none() => search = Some(Search::BellmanFord),
+                "--strict" => strict = true,
+                "--decimal-weights" => decimal_weights = true,
+                "--all-pairs" if report.is_none() => report = Some(Report::AllPairs),
+                "--tree" if report.is_none() => report = Some(Report::Tree),
+                "--components" if report.is_none() => report = Some(Report::Components),
+                "--dot" if report.is_none() => report = Some(Report::Dot),
+                "--format=text" if format.is_none() => format = Some(Format::Text),
+                "--format=json" if format.is_none() => format = Some(Format::Json),
+                _ if filename.is_none() && !arg.starts_with("--") => filename = Some(arg.clone()),
+                _ => return Err(USAGE.to_string()),
+            }
+        }
+        let filename: String = filename.ok_or_else(|| USAGE.to_string())?;
+        let format = format.unwrap_or(if filename.ends_with(".json") {
+            Format::Json
+        } else {
+            Format::Text
+        });
+        return Ok(Options {
+            filename,
+            search: search.unwrap_or(Search::Dijkstra),
+            strict,
+            decimal_weights,
+            report: report.unwrap_or(Report::Routes),
+            format,
+        });
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
     env_logger::init();
     // read input
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        return Err(
-            "Please provide relative file path as input arg, i.e. `$ cargo run <src/test/uk.txt>`"
-                .to_string(),
-        );
-    }
+    let options = Options::new_from_args(&args[1..])?;
 
-    let filename = &args[1];
-    let contents = fs::read_to_string(filename.to_string());
+    let filename = &options.filename;
+    let contents = fs::read_to_string(filename);
     match contents {
         Err(_) => {
             let current_dir =
@@ -30,11 +119,167 @@ fn main() -> Result<(), String> {
             )
         }
         Ok(file_path) => {
-            let mut path_finder = PathFinder::new_from_string(&file_path)?;
-            path_finder.dijkstra_multiple_routes()?;
-            println!("{:?}", path_finder.solutions);
+            if options.decimal_weights {
+                find_routes::<TotalF64>(&file_path, &options)?;
+            } else if options.search == Search::BellmanFord {
+                find_routes::<i64>(&file_path, &options)?;
+            } else {
+                find_routes::<usize>(&file_path, &options)?;
+            }
         }
     }
 
     Ok(())
 }
+
+/// find and print every route in the input, with edge weights of type W
+fn find_routes<W: Weight + Serialize + DeserializeOwned>(
+    file_path: &str,
+    options: &Options,
+) -> Result<(), Box<dyn Error>> {
+    if options.report == Report::AllPairs || options.report == Report::Components {
+        let graph = read_graph::<W>(file_path, options)?;
+        if options.report == Report::AllPairs {
+            print!("{}", AllPairs::new(&graph)?.distances_to_csv(&graph));
+        } else {
+            print_components(&graph);
+        }
+        return Ok(());
+    }
+    if options.report == Report::Tree {
+        let (graph, route_queries) = read_batch::<W>(file_path, options)?;
+        let graph = &graph;
+        let mut search = SearchContext::new(graph.number_of_nodes());
+        let mut sources_printed = Vec::new();
+        for query in route_queries {
+            // as in the default mode, a route that can't be read doesn't stop the rest
+            let start_idx = match query {
+                Ok(query) => query.start_idx,
+                Err(e) if options.strict => return Err(e.to_string().into()),
+                Err(e) => {
+                    println!("{}", e);
+                    continue;
+                }
+            };
+            if sources_printed.contains(&start_idx) {
+                continue;
+            }
+            let tree = if options.search == Search::BellmanFord {
+                search.shortest_path_tree_with_negative_weights(graph, start_idx)?
+            } else {
+                search.shortest_path_tree(graph, start_idx)
+            };
+            if !sources_printed.is_empty() {
+                println!();
+            }
+            print!("{}", tree.to_indented_string(graph));
+            sources_printed.push(start_idx);
+        }
+        return Ok(());
+    }
+
+    let (graph, route_queries) = read_batch::<W>(file_path, options)?;
+    let heuristic = if options.search == Search::AStar {
+        Some(StraightLineHeuristic::new(graph.graph_nodes()))
+    } else {
+        None
+    };
+    let algorithm: Algorithm<W> = match (&options.search, &heuristic) {
+        (Search::AStar, Some(heuristic)) => Algorithm::AStar(heuristic),
+        (Search::Bidirectional, _) => Algorithm::Bidirectional,
+        (Search::BellmanFord, _) => Algorithm::BellmanFord,
+        // a breadth first search finds routes just as short faster, if the weights allow it
+        _ => Algorithm::for_weight_profile(graph.weight_profile()),
+    };
+
+    if options.report == Report::Dot {
+        // every route has to be read to be drawn
+        let routes_to_find = route_queries
+            .into_iter()
+            .map(|query| query.map(|query| (query.start_idx, query.end_idx)))
+            .collect::<Result<Vec<(usize, usize)>, _>>()?;
+        let mut path_finder = PathFinder::new(graph, routes_to_find)?;
+        path_finder.find_multiple_routes(algorithm);
+        print!("{}", path_finder.to_dot());
+        return Ok(());
+    }
+    let outcomes = PathFinder::solve_batch(graph, route_queries, algorithm);
+    if options.format == Format::Json {
+        if options.strict {
+            let is_found = |outcome: &&RouteOutcome<W>| {
+                matches!(
+                    outcome,
+                    RouteOutcome::Found(_) | RouteOutcome::FoundRoutes(_)
+                )
+            };
+            if let Some(outcome) = outcomes.iter().find(|outcome| !is_found(outcome)) {
+                return Err(outcome.to_string().into());
+            }
+        }
+        println!("{}", serde_json::to_string_pretty(&outcomes)?);
+        return Ok(());
+    }
+    for outcome in outcomes {
+        match outcome {
+            RouteOutcome::Found(_) | RouteOutcome::FoundRoutes(_) => println!("{}", outcome),
+            _ if options.strict => return Err(outcome.to_string().into()),
+            _ => println!("{}", outcome),
+        }
+    }
+    return Ok(());
+}
+
+/// read the graph in the given format, allowing negative weights for bellman ford
+fn read_graph<W: Weight + Serialize + DeserializeOwned>(
+    file_path: &str,
+    options: &Options,
+) -> Result<Graph<W>, Box<dyn Error>> {
+    let graph = match (&options.format, &options.search) {
+        (Format::Text, Search::BellmanFord) => {
+            Graph::<W>::new_from_string_with_signed_weights(file_path)?
+        }
+        (Format::Text, _) => Graph::<W>::new_from_string_with_weights(file_path)?,
+        (Format::Json, Search::BellmanFord) => {
+            Graph::<W>::new_from_json_with_signed_weights(file_path)?
+        }
+        (Format::Json, _) => Graph::<W>::new_from_json(file_path)?,
+    };
+    return Ok(graph);
+}
+
+/// read the graph and each of the routes in the given format, allowing negative weights for
+/// bellman ford
+fn read_batch<W: Weight + Serialize + DeserializeOwned>(
+    file_path: &str,
+    options: &Options,
+) -> Result<RouteBatch<W>, Box<dyn Error>> {
+    let batch = match (&options.format, &options.search) {
+        (Format::Text, Search::BellmanFord) => {
+            PathFinder::<W>::read_batch_from_string_with_signed_weights(file_path)?
+        }
+        (Format::Text, _) => PathFinder::<W>::read_batch_from_string(file_path)?,
+        (Format::Json, Search::BellmanFord) => {
+            PathFinder::<W>::read_batch_from_json_with_signed_weights(file_path)?
+        }
+        (Format::Json, _) => PathFinder::<W>::read_batch_from_json(file_path)?,
+    };
+    return Ok(batch);
+}
+
+/// list the nodes of each component, strongly connected if any of the edges are directed
+fn print_components<W: Weight>(graph: &Graph<W>) {
+    let (kind, components) = if graph.edges().iter().any(|edge| edge.is_directed) {
+        ("strongly connected", graph.strongly_connected_components())
+    } else {
+        ("connected", graph.connected_components())
+    };
+    let plural = if components.len() == 1 { "" } else { "s" };
+    println!("{} {} component{}", components.len(), kind, plural);
+    for (component_number, component) in components.iter().enumerate() {
+        let node_names: Vec<&str> = component
+            .iter()
+            .map(|&node_idx| graph.graph_nodes()[node_idx].node_name.as_str())
+            .collect();
+        println!("{}. {}", component_number + 1, node_names.join(", "));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::route::Route;
+use crate::search_context::SearchContext;
+use crate::weight::Weight;
+
+/// The closest of a set of sources (i.e. hospitals or depots) to every node, as found by a single
+/// dijkstra search started from all of the sources at once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearestSources<W = usize> {
+    pub sources: Vec<usize>,
+    /// `nearest[node_idx]` is the closest source and the distance to it, or None if the node
+    /// can't reach any source. When two sources are equally close either may be chosen.
+    pub nearest: Vec<Option<(usize, W)>>,
+    /// `next_hops[node_idx]` is the node after it on the route to its nearest source, or None
+    /// for the sources and the nodes that can't reach one
+    pub next_hops: Vec<Option<usize>>,
+}
+
+impl<W: Weight> SearchContext<W> {
+    /// find the nearest source to every node, measured along the edges from the node to the
+    /// source. The search starts from the sources and follows the edges backwards, so one way
+    /// edges are only used in the direction they go.
+    pub fn nearest_sources(
+        &mut self,
+        graph: &Graph<W>,
+        sources: &[usize],
+    ) -> Result<NearestSources<W>, DijkstraError> {
+        if let Some(&node_idx) = sources.iter().find(|&&idx| idx >= graph.number_of_nodes) {
+            return Err(DijkstraError::InvalidNodeIndex { node_idx });
+        }
+        let settled = self.dijkstra_from_sources(graph, sources, true);
+        let mut nearest_sources = NearestSources {
+            sources: sources.to_vec(),
+            nearest: vec![None; graph.number_of_nodes],
+            next_hops: vec![None; graph.number_of_nodes],
+        };
+        // every node is settled after the node after it, which already knows its source
+        for node_idx in settled {
+            let node = self.nodes_visited[node_idx].expect("Expected settled nodes to be reached");
+            let source_idx = if node.parent_idx == node_idx {
+                node_idx
+            } else {
+                nearest_sources.next_hops[node_idx] = Some(node.parent_idx);
+                let (source_idx, _) = nearest_sources.nearest[node.parent_idx]
+                    .expect("Expected the next hop to be settled before the node");
+                source_idx
+            };
+            nearest_sources.nearest[node_idx] = Some((source_idx, node.dist_to_node));
+        }
+        return Ok(nearest_sources);
+    }
+}
+
+impl<W: Weight> NearestSources<W> {
+    /// the nodes of the shortest route from the node to its nearest source, or None if it can't
+    /// reach one
+    pub fn path_to_nearest_source(&self, node_idx: usize) -> Option<Vec<usize>> {
+        self.nearest[node_idx]?;
+        let mut nodes_in_order = vec![node_idx];
+        let mut idx = node_idx;
+        while let Some(next_idx) = self.next_hops[idx] {
+            nodes_in_order.push(next_idx);
+            idx = next_idx;
+        }
+        return Some(nodes_in_order);
+    }
+
+    /// the shortest route from the node to its nearest source, with its node names and leg weights
+    pub fn route_to_nearest_source(
+        &self,
+        graph: &Graph<W>,
+        node_idx: usize,
+    ) -> Result<Route<W>, DijkstraError> {
+        let nodes_in_order = match self.path_to_nearest_source(node_idx) {
+            Some(nodes_in_order) => nodes_in_order,
+            // there is no nearest source to report, so any of them will do
+            None => {
+                let end_idx = self.sources.first().copied().unwrap_or(node_idx);
+                return Err(DijkstraError::disconnected(graph, node_idx, end_idx));
+            }
+        };
+        return Route::new(graph, nodes_in_order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    #[test]
+    fn nearest_source_matches_dijkstra_to_each_source() {
+        let contents = fs::read_to_string("src/test/uk.txt").unwrap();
+        let graph = Graph::new_from_string(&contents).unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let sources = [0, 3, 8];
+        let nearest_sources = context.nearest_sources(&graph, &sources).unwrap();
+        for node_idx in 0..graph.number_of_nodes {
+            let closest = sources
+                .iter()
+                .map(|&source_idx| context.dijkstra(&graph, node_idx, source_idx).unwrap().0)
+                .min();
+            let (source_idx, dist) = nearest_sources.nearest[node_idx].unwrap();
+            assert_eq!(Some(dist), closest);
+            assert_eq!(
+                context.dijkstra(&graph, node_idx, source_idx).unwrap().0,
+                dist
+            );
+            let nodes_in_order = nearest_sources.path_to_nearest_source(node_idx).unwrap();
+            assert_eq!(nodes_in_order.first(), Some(&node_idx));
+            assert_eq!(nodes_in_order.last(), Some(&source_idx));
+            assert_eq!(
+                nearest_sources
+                    .route_to_nearest_source(&graph, node_idx)
+                    .unwrap()
+                    .total_distance,
+                dist
+            );
+        }
+        for source_idx in sources {
+            assert_eq!(nearest_sources.nearest[source_idx], Some((source_idx, 0)));
+            assert_eq!(
+                nearest_sources.path_to_nearest_source(source_idx),
+                Some(vec![source_idx])
+            );
+        }
+    }
+    #[test]
+    fn one_way_edges_are_followed_towards_the_source() {
+        let graph = Graph::new_from_string(
+            "7\nA\nB\nC\nD\nE\nF\nG\n\n6 directed\nA B 1\nB C 5\nC D 2\nE D 1\nF A 1\nA G 1\n\nA C",
+        )
+        .unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        // a repeated source is only searched from once
+        let nearest_sources = context.nearest_sources(&graph, &[0, 3, 0]).unwrap();
+        // B is 1 from A, but can only get to D
+        assert_eq!(
+            nearest_sources.nearest,
+            vec![
+                Some((0, 0)),
+                Some((3, 7)),
+                Some((3, 2)),
+                Some((3, 0)),
+                Some((3, 1)),
+                Some((0, 1)),
+                None
+            ]
+        );
+        assert_eq!(
+            nearest_sources.path_to_nearest_source(1),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(nearest_sources.path_to_nearest_source(6), None);
+        assert_eq!(
+            nearest_sources.route_to_nearest_source(&graph, 6),
+            Err(DijkstraError::Disconnected {
+                start_idx: 6,
+                end_idx: 0,
+                start_name: "G".to_string(),
+                end_name: "A".to_string()
+            })
+        );
+        assert!(context
+            .nearest_sources(&graph, &[])
+            .unwrap()
+            .nearest
+            .iter()
+            .all(|n| n.is_none()));
+        assert_eq!(
+            context.nearest_sources(&graph, &[0, 9]),
+            Err(DijkstraError::InvalidNodeIndex { node_idx: 9 })
+        );
+    }
+}
@@ -1,45 +1,89 @@
+use crate::error::DijkstraError;
+use crate::weight::{Weight, WeightParseError};
+
 use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GraphNode {
+    /// the position of the node in the node list, so it isn't written out separately
+    #[serde(skip)]
     pub index: usize,
+    #[serde(rename = "name")]
     pub node_name: String,
+    /// optional (x, y) position of the node, used by the straight-line A* heuristic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coordinates: Option<(f64, f64)>,
+    /// any other information given about the node, i.e. in JSON, which the searches ignore
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "crate::json::attributes_from_json"
+    )]
+    pub attributes: BTreeMap<String, String>,
 }
 
 impl GraphNode {
-    pub(crate) fn new(idx_: usize, name_: String) -> GraphNode {
+    pub fn new(idx_: usize, name_: String) -> GraphNode {
+        return GraphNode {
+            index: idx_,
+            node_name: name_,
+            coordinates: None,
+            attributes: BTreeMap::new(),
+        };
+    }
+
+    pub fn new_with_coordinates(idx_: usize, name_: String, x: f64, y: f64) -> GraphNode {
         return GraphNode {
             index: idx_,
             node_name: name_,
+            coordinates: Some((x, y)),
+            attributes: BTreeMap::new(),
         };
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub struct Edge {
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Edge<W = usize> {
+    #[serde(rename = "from")]
     pub index_first: usize,
+    #[serde(rename = "to")]
     pub index_second: usize,
-    pub weight: usize,
-    pub is_traversed: bool,
+    pub weight: W,
+    /// a directed edge can only be travelled from index_first to index_second
+    #[serde(rename = "directed", default)]
+    pub is_directed: bool,
 }
 
-impl Edge {
-    pub(crate) fn new(start_index: usize, end_index: usize, weight: usize) -> Edge {
+impl<W: Weight> Edge<W> {
+    /// an edge that can be travelled in both directions
+    pub fn new(start_index: usize, end_index: usize, weight: W) -> Edge<W> {
         return Edge {
             index_first: start_index,
             index_second: end_index,
             weight,
-            is_traversed: false,
+            is_directed: false,
+        };
+    }
+
+    /// an edge that can only be travelled from start to end
+    pub fn new_directed(start_index: usize, end_index: usize, weight: W) -> Edge<W> {
+        return Edge {
+            is_directed: true,
+            ..Edge::new(start_index, end_index, weight)
         };
     }
 }
 
 pub fn split_contents_into_nodes_edges_routes(
     contents: String,
-) -> Result<(String, String, String), String> {
+) -> Result<(String, String, String), DijkstraError> {
     let data: Vec<&str> = contents.split("\n\n").collect();
     if data.len() != 3 {
-        return Err("Invalid file format.".to_string());
+        return Err(DijkstraError::InvalidFileFormat {
+            sections_found: data.len(),
+        });
     }
     let node_data = data[0].to_string();
     let edge_data = data[1].to_string();
@@ -48,36 +92,96 @@ pub fn split_contents_into_nodes_edges_routes(
     return Ok((node_data, edge_data, routes_to_find));
 }
 
-pub fn get_node_index_from_node_name(
+/// the number of lines in the file before the edge section and before the route section,
+/// used to turn line numbers within a section into line numbers within the file
+pub fn lines_before_edges_and_routes(node_data: &str, edge_data: &str) -> (usize, usize) {
+    // each section is followed by an empty line
+    let lines_before_edges = node_data.split("\n").count() + 1;
+    let lines_before_routes = lines_before_edges + edge_data.split("\n").count() + 1;
+    return (lines_before_edges, lines_before_routes);
+}
+
+pub fn get_node_index_from_node_name(node_name: &str, graph_nodes: &[GraphNode]) -> Option<usize> {
+    return graph_nodes
+        .iter()
+        .find(|&x| x.node_name == node_name)
+        .map(|node| node.index);
+}
+
+/// read the weight of the edge on the line, which can only be negative if that is allowed
+pub(crate) fn parse_weight_on_line<W: Weight>(
+    weight_str: &str,
+    allow_negative_weights: bool,
+    line: usize,
+    text: &str,
+) -> Result<W, DijkstraError> {
+    let weight = W::parse_weight(weight_str).map_err(|reason| match reason {
+        WeightParseError::NotAnInteger => DijkstraError::NonIntegerWeight {
+            line,
+            text: text.to_string(),
+            weight: weight_str.to_string(),
+        },
+        WeightParseError::Negative => DijkstraError::NegativeWeight {
+            line,
+            text: text.to_string(),
+            weight: weight_str.to_string(),
+        },
+        _ => DijkstraError::InvalidWeight {
+            line,
+            text: text.to_string(),
+            weight: weight_str.to_string(),
+            reason,
+        },
+    })?;
+    if !allow_negative_weights && weight < W::zero() {
+        return Err(DijkstraError::NegativeWeight {
+            line,
+            text: text.to_string(),
+            weight: weight_str.to_string(),
+        });
+    }
+    return Ok(weight);
+}
+
+/// as get_node_index_from_node_name, but a missing node is an error for the given line
+fn find_node_on_line(
     node_name: &str,
-    graph_nodes: &Vec<GraphNode>,
-) -> Result<usize, String> {
-    let graph_node = graph_nodes.iter().find(|&x| x.node_name == node_name);
-    match graph_node {
-        None => {
-            return Err(format!(
-            "Nodes in edges should be present in node list. Node {} (possibly others) not found.",
-            node_name
-        ))
+    graph_nodes: &[GraphNode],
+    line: usize,
+    text: &str,
+) -> Result<usize, DijkstraError> {
+    return get_node_index_from_node_name(node_name, graph_nodes).ok_or_else(|| {
+        DijkstraError::UnknownNode {
+            line,
+            text: text.to_string(),
+            node_name: node_name.to_string(),
         }
-        Some(node) => return Ok(node.index),
-    }
+    });
 }
 
-pub fn parse_graph_nodes_from_string(node_data: &str) -> Result<Vec<GraphNode>, String> {
+pub fn parse_graph_nodes_from_string(node_data: &str) -> Result<Vec<GraphNode>, DijkstraError> {
     let nodes: Vec<&str> = node_data.split("\n").collect();
-    let num_nodes: usize = nodes[0]
-        .parse::<usize>()
-        .expect("Expect an integer number of nodes.");
+    let num_nodes: usize =
+        nodes[0]
+            .parse::<usize>()
+            .map_err(|_| DijkstraError::InvalidNodeCount {
+                line: 1,
+                text: nodes[0].to_string(),
+            })?;
 
     if nodes.len() != num_nodes + 1 {
-        return Err("Unexpected number of nodes".to_string());
+        return Err(DijkstraError::UnexpectedNodeCount {
+            line: 1,
+            expected: num_nodes,
+            actual: nodes.len() - 1,
+        });
     }
 
     let mut graph_nodes = Vec::with_capacity(num_nodes);
 
-    for i in 1..(num_nodes + 1) {
-        graph_nodes.push(GraphNode::new(i - 1, nodes[i].to_string()));
+    for (i, node_line) in nodes.iter().skip(1).enumerate() {
+        // the first line is the number of nodes, and lines count from 1
+        graph_nodes.push(parse_graph_node(i, node_line, i + 2)?);
     }
 
     debug!("graph nodes: {:?}", graph_nodes);
@@ -85,108 +189,209 @@ pub fn parse_graph_nodes_from_string(node_data: &str) -> Result<Vec<GraphNode>,
     return Ok(graph_nodes);
 }
 
-pub fn parse_edges_from_string(
+/// a node is either just a name, or a name followed by its x and y coordinates
+fn parse_graph_node(idx: usize, node_line: &str, line: usize) -> Result<GraphNode, DijkstraError> {
+    let node_info: Vec<&str> = node_line.split(" ").collect();
+    let invalid_node = DijkstraError::InvalidNode {
+        line,
+        text: node_line.to_string(),
+    };
+    match node_info[..] {
+        [name] if !name.is_empty() => return Ok(GraphNode::new(idx, name.to_string())),
+        [name, x, y] => {
+            let x = x.parse::<f64>();
+            let y = y.parse::<f64>();
+            match (x, y) {
+                (Ok(x), Ok(y)) if x.is_finite() && y.is_finite() => {
+                    return Ok(GraphNode::new_with_coordinates(idx, name.to_string(), x, y))
+                }
+                _ => return Err(invalid_node),
+            }
+        }
+        _ => return Err(invalid_node),
+    }
+}
+
+/// Edges are given as `A B 5`, `A -> B 5` (directed) or `A <-> B 5` (bidirectional).
+/// `A B 5` is bidirectional, unless the number of edges is followed by `directed`, i.e. `4 directed`.
+/// The weights are read as `W`, i.e. `A B 1.5` for `TotalF64` weights, and can't be negative.
+pub fn parse_edges_from_string<W: Weight>(
+    edge_data: &str,
+    graph_nodes: &[GraphNode],
+) -> Result<Vec<Edge<W>>, DijkstraError> {
+    return parse_edges(edge_data, graph_nodes, false);
+}
+
+/// as parse_edges_from_string, but negative weights are allowed, i.e. `A B -3` for `i64` weights
+pub fn parse_signed_edges_from_string<W: Weight>(
     edge_data: &str,
-    graph_nodes: &Vec<GraphNode>,
-) -> Result<Vec<Edge>, String> {
+    graph_nodes: &[GraphNode],
+) -> Result<Vec<Edge<W>>, DijkstraError> {
+    return parse_edges(edge_data, graph_nodes, true);
+}
+
+fn parse_edges<W: Weight>(
+    edge_data: &str,
+    graph_nodes: &[GraphNode],
+    allow_negative_weights: bool,
+) -> Result<Vec<Edge<W>>, DijkstraError> {
     let edges: Vec<&str> = edge_data.split("\n").collect();
-    let num_edges: usize = edges[0]
+    let edge_header: Vec<&str> = edges[0].split(" ").collect();
+    let invalid_edge_count = || DijkstraError::InvalidEdgeCount {
+        line: 1,
+        text: edges[0].to_string(),
+    };
+    let num_edges: usize = edge_header[0]
         .parse::<usize>()
-        .expect("Expect an integer number of edges.");
+        .map_err(|_| invalid_edge_count())?;
+    let is_directed_by_default = match edge_header[1..] {
+        [] => false,
+        ["directed"] => true,
+        _ => return Err(invalid_edge_count()),
+    };
 
     if num_edges != edges.len() - 1 {
-        return Err(format!(
-            "Unexpected number of edges. Expected: {}, actual: {}",
-            num_edges,
-            edges.len() - 1,
-        ));
-    }
-
-    let mut useful_edges = Vec::with_capacity(num_edges - 1);
-
-    for idx in 1..edges.len() {
-        let edge_info: Vec<&str> = edges[idx].split(" ").collect();
-        if edge_info.len() != 3 {
-            return Err(format!(
-                "Edge {:?} is invalid. Please check the input.",
-                edge_info
-            ));
-        }
-        let start_edge = edge_info[0];
-        let end_edge = edge_info[1];
-        let edge_weight = edge_info[2].parse::<usize>().expect(&format!(
-            "Distance between edges should be an integer, {} found.",
-            edge_info[2]
-        ));
+        return Err(DijkstraError::UnexpectedEdgeCount {
+            line: 1,
+            expected: num_edges,
+            actual: edges.len() - 1,
+        });
+    }
+
+    let mut useful_edges = Vec::with_capacity(num_edges);
+
+    for (i, edge) in edges.iter().enumerate().skip(1) {
+        let line = i + 1;
+        let edge_info: Vec<&str> = edge.split(" ").collect();
+        let (start_edge, end_edge, weight_str, is_directed) = match edge_info[..] {
+            [start, end, weight] => (start, end, weight, is_directed_by_default),
+            [start, "->", end, weight] => (start, end, weight, true),
+            [start, "<->", end, weight] => (start, end, weight, false),
+            _ => {
+                return Err(DijkstraError::InvalidEdge {
+                    line,
+                    text: edge.to_string(),
+                })
+            }
+        };
+        let edge_weight = parse_weight_on_line(weight_str, allow_negative_weights, line, edge)?;
 
-        let start_index = get_node_index_from_node_name(start_edge, graph_nodes)?;
-        let end_index = get_node_index_from_node_name(end_edge, graph_nodes)?;
+        let start_index = find_node_on_line(start_edge, graph_nodes, line, edge)?;
+        let end_index = find_node_on_line(end_edge, graph_nodes, line, edge)?;
 
         if start_index != end_index {
-            useful_edges.push(Edge::new(start_index, end_index, edge_weight));
+            if is_directed {
+                useful_edges.push(Edge::new_directed(start_index, end_index, edge_weight));
+            } else {
+                useful_edges.push(Edge::new(start_index, end_index, edge_weight));
+            }
         }
     }
 
     return Ok(useful_edges);
 }
 
+/// One line of the routes section, i.e. `A B` or `A B 3`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RouteQuery {
+    #[serde(rename = "from")]
+    pub start_idx: usize,
+    #[serde(rename = "to")]
+    pub end_idx: usize,
+    /// how many of the shortest loopless routes to find, if given after the node names
+    #[serde(rename = "k", default, skip_serializing_if = "Option::is_none")]
+    pub number_of_routes: Option<usize>,
+}
+
+/// each route is a start and end node name on its own line, empty lines are ignored.
+/// The number of routes to find, if given, is dropped.
 pub fn parse_routes_from_string(
     routes_to_find: &str,
-    graph_nodes: &Vec<GraphNode>,
-) -> Result<Vec<(usize, usize)>, String> {
-    let routes: Vec<&str> = routes_to_find.trim().split("\n").collect();
-    let mut parsed_routes = Vec::with_capacity(4);
-    for route in routes {
-        let route_names: Vec<&str> = route.split(" ").collect();
-        if route_names.len() != 2 {
-            return Err(format!(
-                "Route {:?} is invalid. Please check the input.",
-                route_names
-            ));
-        }
-        let start_str = route_names[0];
-        let end_str = route_names[1];
-        if start_str == end_str {
-            return Err(format!(
-                "Route is self referential. Dist from {} to {} = 0",
-                start_str, end_str
-            ));
-        }
+    graph_nodes: &[GraphNode],
+) -> Result<Vec<(usize, usize)>, DijkstraError> {
+    return parse_route_queries_from_string(routes_to_find, graph_nodes)
+        .into_iter()
+        .map(|query| query.map(|query| (query.start_idx, query.end_idx)))
+        .collect();
+}
 
-        let start_idx = get_node_index_from_node_name(start_str, graph_nodes)?;
-        let end_idx = get_node_index_from_node_name(end_str, graph_nodes)?;
-        parsed_routes.push((start_idx, end_idx));
+/// as parse_routes_from_string, but an invalid route does not stop the routes after it from
+/// being parsed: each line gets its own result. A route can be followed by how many of the
+/// shortest routes to find, i.e. `A B 3`.
+pub fn parse_route_queries_from_string(
+    routes_to_find: &str,
+    graph_nodes: &[GraphNode],
+) -> Vec<Result<RouteQuery, DijkstraError>> {
+    return routes_to_find
+        .split("\n")
+        .enumerate()
+        .filter(|(_, route)| !route.trim().is_empty())
+        .map(|(i, route)| parse_route(route, graph_nodes, i + 1))
+        .collect();
+}
+
+fn parse_route(
+    route: &str,
+    graph_nodes: &[GraphNode],
+    line: usize,
+) -> Result<RouteQuery, DijkstraError> {
+    let invalid_route = || DijkstraError::InvalidRoute {
+        line,
+        text: route.to_string(),
+    };
+    let route_info: Vec<&str> = route.split(" ").collect();
+    let (start_str, end_str, number_of_routes) = match route_info[..] {
+        [start, end] => (start, end, None),
+        [start, end, k] => match k.parse::<usize>() {
+            Ok(k) if k > 0 => (start, end, Some(k)),
+            _ => return Err(invalid_route()),
+        },
+        _ => return Err(invalid_route()),
+    };
+    if start_str == end_str {
+        return Err(DijkstraError::SelfReferentialRoute {
+            line,
+            text: route.to_string(),
+            node_name: start_str.to_string(),
+        });
     }
 
-    return Ok(parsed_routes);
+    let start_idx = find_node_on_line(start_str, graph_nodes, line, route)?;
+    let end_idx = find_node_on_line(end_str, graph_nodes, line, route)?;
+    return Ok(RouteQuery {
+        start_idx,
+        end_idx,
+        number_of_routes,
+    });
 }
 
 #[cfg(test)]
 mod input_tests {
     use super::*;
+    use crate::weight::TotalF64;
 
     #[test]
     fn test_parsing_data_from_incorrect_format() {
         let incorrect_contents: String = "incorrectly formatted input".to_string();
         assert_eq!(
-            Err("Invalid file format.".to_string()),
+            Err(DijkstraError::InvalidFileFormat { sections_found: 1 }),
             split_contents_into_nodes_edges_routes(incorrect_contents)
         );
         let contents_no_routes: String = "2\nA\nB\n\n1\nA B 1".to_string();
         assert_eq!(
-            Err("Invalid file format.".to_string()),
+            Err(DijkstraError::InvalidFileFormat { sections_found: 2 }),
             split_contents_into_nodes_edges_routes(contents_no_routes)
         );
         let contents_wrong_delimiters_edge =
             "3\nI\nG\nE\n\n4\nI G 167\nI E 158\nG,E,45\nI G 17\n\nG E\nE I\n\n".to_string();
         assert_eq!(
-            Err("Invalid file format.".to_string()),
+            Err(DijkstraError::InvalidFileFormat { sections_found: 4 }),
             split_contents_into_nodes_edges_routes(contents_wrong_delimiters_edge)
         );
         let contents_wrong_delimiters_route =
             "3\nI\nG\nE\n\n4\nI G 167\nI E 158\nG E 45\nI G 17\n\nG,E\nE I\n\n".to_string();
         assert_eq!(
-            Err("Invalid file format.".to_string()),
+            Err(DijkstraError::InvalidFileFormat { sections_found: 4 }),
             split_contents_into_nodes_edges_routes(contents_wrong_delimiters_route)
         );
     }
@@ -203,4 +408,231 @@ mod input_tests {
         assert_eq!(start_idx, 1);
         assert_eq!(end_idx, 2);
     }
+    #[test]
+    fn test_parsing_nodes_with_coordinates() {
+        let graph_nodes = parse_graph_nodes_from_string("3\nA 0 0\nB 3 4.5\nC").unwrap();
+        assert_eq!(graph_nodes[0].coordinates, Some((0.0, 0.0)));
+        assert_eq!(graph_nodes[1].coordinates, Some((3.0, 4.5)));
+        assert_eq!(graph_nodes[2], GraphNode::new(2, "C".to_string()));
+
+        assert_eq!(
+            parse_graph_nodes_from_string("1\nA 0 north"),
+            Err(DijkstraError::InvalidNode {
+                line: 2,
+                text: "A 0 north".to_string()
+            })
+        );
+        assert_eq!(
+            parse_graph_nodes_from_string("1\nA 0"),
+            Err(DijkstraError::InvalidNode {
+                line: 2,
+                text: "A 0".to_string()
+            })
+        );
+    }
+    #[test]
+    fn test_parsing_edge_directions() {
+        let graph_nodes = parse_graph_nodes_from_string("3\nA\nB\nC").unwrap();
+        let edges = parse_edges_from_string("3\nA B 1\nA -> B 2\nB <-> C 3", &graph_nodes);
+        assert_eq!(
+            edges,
+            Ok(vec![
+                Edge::new(0, 1, 1),
+                Edge::new_directed(0, 1, 2),
+                Edge::new(1, 2, 3)
+            ])
+        );
+
+        let edges = parse_edges_from_string("2 directed\nA B 1\nB <-> C 3", &graph_nodes);
+        assert_eq!(
+            edges,
+            Ok(vec![Edge::new_directed(0, 1, 1), Edge::new(1, 2, 3)])
+        );
+
+        assert_eq!(
+            parse_edges_from_string::<usize>("1 sideways\nA B 1", &graph_nodes),
+            Err(DijkstraError::InvalidEdgeCount {
+                line: 1,
+                text: "1 sideways".to_string()
+            })
+        );
+        assert_eq!(
+            parse_edges_from_string::<usize>("1\nA <- B 1", &graph_nodes),
+            Err(DijkstraError::InvalidEdge {
+                line: 2,
+                text: "A <- B 1".to_string()
+            })
+        );
+    }
+    #[test]
+    fn test_bad_numbers_are_errors_not_panics() {
+        assert_eq!(
+            parse_graph_nodes_from_string("two\nA\nB"),
+            Err(DijkstraError::InvalidNodeCount {
+                line: 1,
+                text: "two".to_string()
+            })
+        );
+        assert_eq!(
+            parse_graph_nodes_from_string("3\nA\nB"),
+            Err(DijkstraError::UnexpectedNodeCount {
+                line: 1,
+                expected: 3,
+                actual: 2
+            })
+        );
+        let graph_nodes = parse_graph_nodes_from_string("2\nA\nB").unwrap();
+        assert_eq!(
+            parse_edges_from_string::<usize>("many\nA B 1", &graph_nodes),
+            Err(DijkstraError::InvalidEdgeCount {
+                line: 1,
+                text: "many".to_string()
+            })
+        );
+        assert_eq!(
+            parse_edges_from_string::<usize>("2\nA B 1\nB A far", &graph_nodes),
+            Err(DijkstraError::NonIntegerWeight {
+                line: 3,
+                text: "B A far".to_string(),
+                weight: "far".to_string()
+            })
+        );
+    }
+    #[test]
+    fn test_route_errors() {
+        let graph_nodes = parse_graph_nodes_from_string("2\nA\nB").unwrap();
+        assert_eq!(
+            parse_routes_from_string("A B\nB B", &graph_nodes),
+            Err(DijkstraError::SelfReferentialRoute {
+                line: 2,
+                text: "B B".to_string(),
+                node_name: "B".to_string()
+            })
+        );
+        assert_eq!(
+            parse_routes_from_string("A B C", &graph_nodes),
+            Err(DijkstraError::InvalidRoute {
+                line: 1,
+                text: "A B C".to_string()
+            })
+        );
+        assert_eq!(
+            parse_routes_from_string("A B\n\nA Z\n", &graph_nodes),
+            Err(DijkstraError::UnknownNode {
+                line: 3,
+                text: "A Z".to_string(),
+                node_name: "Z".to_string()
+            })
+        );
+    }
+    #[test]
+    fn test_lines_before_sections() {
+        let contents = "2\nA\nB\n\n1\nA B 1\n\nA B";
+        let (node_data, edge_data, _) =
+            split_contents_into_nodes_edges_routes(contents.to_string()).unwrap();
+        // the edge section starts on line 5, and the routes on line 8
+        assert_eq!(
+            lines_before_edges_and_routes(&node_data, &edge_data),
+            (4, 7)
+        );
+    }
+    #[test]
+    fn test_route_queries_keep_going_after_errors() {
+        let graph_nodes = parse_graph_nodes_from_string("2\nA\nB").unwrap();
+        assert_eq!(
+            parse_route_queries_from_string("B B\nA Z\nA B\n", &graph_nodes),
+            vec![
+                Err(DijkstraError::SelfReferentialRoute {
+                    line: 1,
+                    text: "B B".to_string(),
+                    node_name: "B".to_string()
+                }),
+                Err(DijkstraError::UnknownNode {
+                    line: 2,
+                    text: "A Z".to_string(),
+                    node_name: "Z".to_string()
+                }),
+                Ok(RouteQuery {
+                    start_idx: 0,
+                    end_idx: 1,
+                    number_of_routes: None
+                }),
+            ]
+        );
+        assert_eq!(
+            parse_route_queries_from_string("A B 3\nB A 0\nB A x", &graph_nodes),
+            vec![
+                Ok(RouteQuery {
+                    start_idx: 0,
+                    end_idx: 1,
+                    number_of_routes: Some(3)
+                }),
+                Err(DijkstraError::InvalidRoute {
+                    line: 2,
+                    text: "B A 0".to_string()
+                }),
+                Err(DijkstraError::InvalidRoute {
+                    line: 3,
+                    text: "B A x".to_string()
+                }),
+            ]
+        );
+        assert_eq!(
+            parse_routes_from_string("A B 3", &graph_nodes),
+            Ok(vec![(0, 1)])
+        );
+    }
+    #[test]
+    fn test_parsing_decimal_weights() {
+        let graph_nodes = parse_graph_nodes_from_string("2\nA\nB").unwrap();
+        assert_eq!(
+            parse_edges_from_string::<TotalF64>("1\nA B 1.5", &graph_nodes),
+            Ok(vec![Edge::new(0, 1, TotalF64::new(1.5).unwrap())])
+        );
+        assert_eq!(
+            parse_edges_from_string::<TotalF64>("1\nA B NaN", &graph_nodes),
+            Err(DijkstraError::InvalidWeight {
+                line: 2,
+                text: "A B NaN".to_string(),
+                weight: "NaN".to_string(),
+                reason: WeightParseError::NaN
+            })
+        );
+        assert_eq!(
+            parse_edges_from_string::<TotalF64>("1\nA B -0.5", &graph_nodes),
+            Err(DijkstraError::NegativeWeight {
+                line: 2,
+                text: "A B -0.5".to_string(),
+                weight: "-0.5".to_string()
+            })
+        );
+        assert_eq!(
+            parse_edges_from_string::<i64>("1\nA B -2", &graph_nodes),
+            Err(DijkstraError::NegativeWeight {
+                line: 2,
+                text: "A B -2".to_string(),
+                weight: "-2".to_string()
+            })
+        );
+        assert_eq!(
+            parse_edges_from_string::<usize>("1\nA B -2", &graph_nodes),
+            Err(DijkstraError::NegativeWeight {
+                line: 2,
+                text: "A B -2".to_string(),
+                weight: "-2".to_string()
+            })
+        );
+        assert_eq!(
+            parse_signed_edges_from_string::<i64>("1\nA B -2", &graph_nodes),
+            Ok(vec![Edge::new(0, 1, -2)])
+        );
+        assert_eq!(
+            parse_edges_from_string::<usize>("1\nA B 1.5", &graph_nodes),
+            Err(DijkstraError::NonIntegerWeight {
+                line: 2,
+                text: "A B 1.5".to_string(),
+                weight: "1.5".to_string()
+            })
+        );
+    }
 }
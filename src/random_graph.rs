@@ -0,0 +1,46 @@
+//! Pseudo-random graphs, for checking one search against another in the tests and benchmarks.
+//! Only public items are used through `crate::`, so that the benchmarks can include this file
+//! as a module of their own.
+use crate::{Edge, Graph, GraphNode, Weight};
+
+/// A linear congruential generator, so that the graphs are the same between runs
+pub(crate) struct RandomGraphs {
+    seed: u64,
+}
+
+impl RandomGraphs {
+    pub(crate) fn new(seed: u64) -> RandomGraphs {
+        return RandomGraphs { seed };
+    }
+
+    /// a number from 0 up to, but not including, the modulus
+    pub(crate) fn next(&mut self, modulus: usize) -> usize {
+        self.seed = self
+            .seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        return (self.seed >> 33) as usize % modulus;
+    }
+
+    /// a graph of nodes named by their index, and `number_of_edges` random pairs of nodes given
+    /// to `new_edge`, which makes the edge between them or leaves it out with None
+    pub(crate) fn graph<W: Weight>(
+        &mut self,
+        number_of_nodes: usize,
+        number_of_edges: usize,
+        mut new_edge: impl FnMut(&mut RandomGraphs, usize, usize) -> Option<Edge<W>>,
+    ) -> Graph<W> {
+        let graph_nodes = (0..number_of_nodes)
+            .map(|idx| GraphNode::new(idx, idx.to_string()))
+            .collect();
+        let mut edges = Vec::with_capacity(number_of_edges);
+        for _ in 0..number_of_edges {
+            let (from_idx, to_idx) = (self.next(number_of_nodes), self.next(number_of_nodes));
+            if let Some(edge) = new_edge(self, from_idx, to_idx) {
+                edges.push(edge);
+            }
+        }
+        return Graph::new_with_signed_weights(graph_nodes, edges)
+            .expect("Expected random edges to join the nodes");
+    }
+}
@@ -0,0 +1,232 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::weight::Weight;
+
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// A route that has been found through the graph.
+/// The `Display` form is the human readable route, i.e. `A->B->C, dist 12`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Route<W = usize> {
+    pub start_idx: usize,
+    pub end_idx: usize,
+    /// node indexes in the order they are travelled, from start to end
+    pub nodes: Vec<usize>,
+    pub node_names: Vec<String>,
+    /// `leg_weights[i]` is the weight of the edge from `nodes[i]` to `nodes[i + 1]`
+    pub leg_weights: Vec<W>,
+    pub total_distance: W,
+}
+
+impl<W: Weight> Route<W> {
+    /// look up the names and leg weights of the nodes travelled, using the shortest edge
+    /// between each pair of nodes. There must be at least one node in the route.
+    pub(crate) fn new(
+        graph: &Graph<W>,
+        nodes_in_order: Vec<usize>,
+    ) -> Result<Route<W>, DijkstraError> {
+        let mut node_names = Vec::with_capacity(nodes_in_order.len());
+        for &node_idx in &nodes_in_order {
+            match graph.graph_nodes.get(node_idx) {
+                Some(node) if node.index == node_idx => node_names.push(node.node_name.to_string()),
+                _ => return Err(DijkstraError::InvalidNodeIndex { node_idx }),
+            }
+        }
+
+        let mut leg_weights = Vec::with_capacity(nodes_in_order.len().saturating_sub(1));
+        for leg in nodes_in_order.windows(2) {
+            let edge = graph.connections[leg[0]]
+                .iter()
+                .find(|edge| edge.index_second == leg[1])
+                .ok_or(DijkstraError::MissingEdge {
+                    from_idx: leg[0],
+                    to_idx: leg[1],
+                })?;
+            leg_weights.push(edge.weight);
+        }
+
+        let start_idx = nodes_in_order[0];
+        let end_idx = nodes_in_order[nodes_in_order.len() - 1];
+        let total_distance = leg_weights
+            .iter()
+            .try_fold(W::zero(), |total, &weight| total.checked_add(weight))
+            .ok_or_else(|| DijkstraError::distance_overflow(graph, start_idx, end_idx))?;
+        return Ok(Route {
+            start_idx,
+            end_idx,
+            total_distance,
+            nodes: nodes_in_order,
+            node_names,
+            leg_weights,
+        });
+    }
+}
+
+impl<W: Weight> fmt::Display for Route<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(
+            f,
+            "{}, dist {}",
+            self.node_names.join("->"),
+            self.total_distance
+        );
+    }
+}
+
+/// What happened to one of the routes of a batch. Every route gets an outcome, so a failing
+/// route does not stop the routes after it from being found.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum RouteOutcome<W = usize> {
+    Found(Route<W>),
+    /// the shortest routes, shortest first, when more than one route was asked for
+    FoundRoutes(Vec<Route<W>>),
+    /// both nodes exist, but there is no path between them
+    Unreachable {
+        start_name: String,
+        end_name: String,
+    },
+    /// the route refers to a node that is not in the node list
+    UnknownNode {
+        line: usize,
+        node_name: String,
+    },
+    /// the route starts and ends at the same node
+    SelfRoute {
+        line: usize,
+        node_name: String,
+    },
+    /// anything else that stopped the route from being found, i.e. a malformed route line
+    Invalid(DijkstraError),
+}
+
+impl<W: Weight> RouteOutcome<W> {
+    pub(crate) fn new(solution: Result<Route<W>, DijkstraError>) -> RouteOutcome<W> {
+        match solution {
+            Ok(route) => return RouteOutcome::Found(route),
+            Err(DijkstraError::Disconnected {
+                start_name,
+                end_name,
+                ..
+            }) => {
+                return RouteOutcome::Unreachable {
+                    start_name,
+                    end_name,
+                }
+            }
+            Err(DijkstraError::UnknownNode {
+                line, node_name, ..
+            }) => return RouteOutcome::UnknownNode { line, node_name },
+            Err(DijkstraError::SelfReferentialRoute {
+                line, node_name, ..
+            }) => return RouteOutcome::SelfRoute { line, node_name },
+            Err(e) => return RouteOutcome::Invalid(e),
+        }
+    }
+
+    pub(crate) fn new_from_routes(
+        solution: Result<Vec<Route<W>>, DijkstraError>,
+    ) -> RouteOutcome<W> {
+        match solution {
+            Ok(routes) => return RouteOutcome::FoundRoutes(routes),
+            Err(e) => return RouteOutcome::new(Err(e)),
+        }
+    }
+}
+
+/// in JSON an outcome is either `{"routes": [...]}`, with a single route unless more than one was
+/// asked for, or `{"error": "..."}` with the `Display` form of what went wrong
+impl<W: Weight + Serialize> Serialize for RouteOutcome<W> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut outcome = serializer.serialize_map(Some(1))?;
+        match self {
+            RouteOutcome::Found(route) => {
+                outcome.serialize_entry("routes", std::slice::from_ref(route))?
+            }
+            RouteOutcome::FoundRoutes(routes) => outcome.serialize_entry("routes", routes)?,
+            _ => outcome.serialize_entry("error", &self.to_string())?,
+        }
+        return outcome.end();
+    }
+}
+
+impl<W: Weight> fmt::Display for RouteOutcome<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteOutcome::Found(route) => return write!(f, "{}", route),
+            RouteOutcome::FoundRoutes(routes) => {
+                let ranked: Vec<String> = routes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, route)| format!("{}. {}", i + 1, route))
+                    .collect();
+                return write!(f, "{}", ranked.join("\n"));
+            }
+            RouteOutcome::Unreachable {
+                start_name,
+                end_name,
+            } => {
+                return write!(
+                    f,
+                    "{}->{}, no path found. Are the start and end disconnected?",
+                    start_name, end_name
+                )
+            }
+            RouteOutcome::UnknownNode { line, node_name } => {
+                return write!(
+                    f,
+                    "Line {}: node {} is not in the node list.",
+                    line, node_name
+                )
+            }
+            RouteOutcome::SelfRoute { line, node_name } => {
+                return write!(
+                    f,
+                    "Line {}: route is self referential. Dist from {} to {} = 0",
+                    line, node_name, node_name
+                )
+            }
+            RouteOutcome::Invalid(e) => return write!(f, "{}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_from_nodes_in_order() {
+        let graph = Graph::new_from_string("3\nA\nB\nC\n\n3\nA B 4\nB C 3\nB C 1\n\nA C").unwrap();
+        let route = Route::new(&graph, vec![0, 1, 2]).unwrap();
+        assert_eq!(
+            route,
+            Route {
+                start_idx: 0,
+                end_idx: 2,
+                nodes: vec![0, 1, 2],
+                node_names: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+                leg_weights: vec![4, 1],
+                total_distance: 5,
+            }
+        );
+        assert_eq!(route.to_string(), "A->B->C, dist 5");
+    }
+    #[test]
+    fn test_route_through_missing_edge_or_node() {
+        let graph = Graph::new_from_string("3\nA\nB\nC\n\n1\nA B 4\n\nA C").unwrap();
+        assert_eq!(
+            Route::new(&graph, vec![0, 2]),
+            Err(DijkstraError::MissingEdge {
+                from_idx: 0,
+                to_idx: 2
+            })
+        );
+        assert_eq!(
+            Route::new(&graph, vec![0, 7]),
+            Err(DijkstraError::InvalidNodeIndex { node_idx: 7 })
+        );
+    }
+}
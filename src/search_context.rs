@@ -0,0 +1,159 @@
+use crate::weight::Weight;
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// All of the state needed while searching for a single route, kept apart from the `Graph` so
+/// that the graph can stay immutable and shared. A context can be reused for any number of
+/// queries against graphs of the same size: only the nodes touched by the previous query are
+/// cleared when the next one starts.
+#[derive(Debug, Clone)]
+pub struct SearchContext<W = usize> {
+    /// `None` for the nodes the current query has not reached
+    pub(crate) nodes_visited: Vec<Option<Node<W>>>,
+    pub(crate) frontier: BinaryHeap<FrontierEntry<W>>,
+    /// the frontier of the breadth first searches, which don't need it ordered by a heap
+    pub(crate) queue: VecDeque<(W, usize)>,
+    /// indexes of the nodes in nodes_visited that have been reached by the current query
+    touched: Vec<usize>,
+    /// the search backwards from the end of the route, for bidirectional queries. Kept between
+    /// queries like the rest of the context, and only created when first needed.
+    pub(crate) reverse: Option<Box<SearchContext<W>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub(crate) struct Node<W = usize> {
+    pub index: usize,
+    pub parent_idx: usize,
+    pub dist_to_node: W,
+}
+
+impl<W: Weight> Node<W> {
+    pub(crate) fn new(index_: usize, parent_idx_: usize, dist_to_node_: W) -> Node<W> {
+        return Node {
+            index: index_,
+            parent_idx: parent_idx_,
+            dist_to_node: dist_to_node_,
+        };
+    }
+}
+
+/// A node waiting in the frontier, with the distance it was reached with.
+/// Entries are never removed when a shorter path to the node is found, instead the
+/// outdated entry is skipped when it is popped (lazy deletion).
+#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+pub(crate) struct FrontierEntry<W> {
+    /// distance to the node + heuristic estimate of the distance remaining
+    pub priority: W,
+    pub dist_to_node: W,
+    pub node_idx: usize,
+}
+
+impl<W: Weight> Ord for FrontierEntry<W> {
+    /// reversed, so that the BinaryHeap (a max-heap) pops the entry with the lowest priority first
+    fn cmp(&self, other: &Self) -> Ordering {
+        return other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| other.dist_to_node.cmp(&self.dist_to_node))
+            .then_with(|| other.node_idx.cmp(&self.node_idx));
+    }
+}
+
+impl<W: Weight> PartialOrd for FrontierEntry<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<W: Weight> SearchContext<W> {
+    pub fn new(number_of_nodes: usize) -> SearchContext<W> {
+        return SearchContext {
+            nodes_visited: vec![None; number_of_nodes],
+            frontier: BinaryHeap::new(),
+            queue: VecDeque::new(),
+            touched: Vec::new(),
+            reverse: None,
+        };
+    }
+
+    /// clear everything left over from the previous query, resizing if the graph has changed
+    pub(crate) fn begin_query(&mut self, number_of_nodes: usize) {
+        if self.nodes_visited.len() != number_of_nodes {
+            *self = SearchContext::new(number_of_nodes);
+            return;
+        }
+        for &node_idx in &self.touched {
+            self.nodes_visited[node_idx] = None;
+        }
+        self.touched.clear();
+        self.frontier.clear();
+        self.queue.clear();
+    }
+
+    /// record the best path found so far to a node
+    pub(crate) fn visit(&mut self, node: Node<W>) {
+        if self.nodes_visited[node.index].is_none() {
+            self.touched.push(node.index);
+        }
+        self.nodes_visited[node.index] = Some(node);
+    }
+
+    /// the shortest distance found so far to a node, or None if it hasn't been reached
+    pub(crate) fn dist_to(&self, node_idx: usize) -> Option<W> {
+        return self.nodes_visited[node_idx].map(|node| node.dist_to_node);
+    }
+
+    /// the entry that would be popped from the frontier next, after dropping any outdated entries
+    pub(crate) fn peek_frontier(&mut self) -> Option<FrontierEntry<W>> {
+        while let Some(&entry) = self.frontier.peek() {
+            if Some(entry.dist_to_node) > self.dist_to(entry.node_idx) {
+                self.frontier.pop();
+                continue;
+            }
+            return Some(entry);
+        }
+        return None;
+    }
+
+    /// go backwards through the nodes to find the parent node. The end node must have been reached.
+    pub(crate) fn route_to(&self, start_idx: usize, end_idx: usize) -> Vec<usize> {
+        let mut idx = end_idx;
+        let mut nodes_in_order: Vec<usize> = vec![end_idx];
+
+        while idx != start_idx {
+            idx = self.nodes_visited[idx]
+                .expect("Expected every node on the route to have been reached")
+                .parent_idx;
+            nodes_in_order.push(idx);
+        }
+
+        nodes_in_order.reverse();
+
+        return nodes_in_order;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_begin_query_only_clears_touched_nodes() {
+        let mut context: SearchContext = SearchContext::new(3);
+        context.visit(Node::new(0, 0, 0));
+        context.visit(Node::new(2, 0, 7));
+        context.visit(Node::new(2, 0, 5));
+        assert_eq!(context.touched, vec![0, 2]);
+        assert_eq!(context.route_to(0, 2), vec![0, 2]);
+        assert_eq!(context.dist_to(2), Some(5));
+        assert_eq!(context.dist_to(1), None);
+
+        context.begin_query(3);
+        assert!(context.touched.is_empty());
+        assert!(context.nodes_visited.iter().all(|node| node.is_none()));
+
+        context.begin_query(5);
+        assert_eq!(context.nodes_visited.len(), 5);
+    }
+}
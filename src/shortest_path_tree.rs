@@ -0,0 +1,195 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::route::Route;
+use crate::search_context::SearchContext;
+use crate::weight::Weight;
+
+/// The shortest routes from one source to every node it can reach, as found by a single
+/// dijkstra search. Any of the routes can be read from the tree without searching again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortestPathTree<W = usize> {
+    pub source_idx: usize,
+    /// `distances[node_idx]` from the source, or None if the node can't be reached
+    pub distances: Vec<Option<W>>,
+    /// `parents[node_idx]` is the node before it on the shortest route from the source, or None
+    /// for the source and the nodes that can't be reached
+    pub parents: Vec<Option<usize>>,
+}
+
+impl<W: Weight> SearchContext<W> {
+    /// run dijkstra from the source until every node it can reach has been settled
+    pub fn shortest_path_tree(
+        &mut self,
+        graph: &Graph<W>,
+        source_idx: usize,
+    ) -> ShortestPathTree<W> {
+        self.dijkstra_from(graph, source_idx);
+        return self.tree_from_nodes_visited(source_idx);
+    }
+
+    /// as shortest_path_tree, but with bellman_ford so that weights can be negative. Fails if a
+    /// negative cycle can be reached from the source.
+    pub fn shortest_path_tree_with_negative_weights(
+        &mut self,
+        graph: &Graph<W>,
+        source_idx: usize,
+    ) -> Result<ShortestPathTree<W>, DijkstraError> {
+        self.bellman_ford_from(graph, source_idx)?;
+        return Ok(self.tree_from_nodes_visited(source_idx));
+    }
+
+    fn tree_from_nodes_visited(&self, source_idx: usize) -> ShortestPathTree<W> {
+        let number_of_nodes = self.nodes_visited.len();
+        let mut tree = ShortestPathTree {
+            source_idx,
+            distances: vec![None; number_of_nodes],
+            parents: vec![None; number_of_nodes],
+        };
+        for (node_idx, node) in self.nodes_visited.iter().enumerate() {
+            if let Some(node) = node {
+                tree.distances[node_idx] = Some(node.dist_to_node);
+                if node_idx != source_idx {
+                    tree.parents[node_idx] = Some(node.parent_idx);
+                }
+            }
+        }
+        return tree;
+    }
+}
+
+impl<W: Weight> ShortestPathTree<W> {
+    /// the nodes of the shortest route from the source to the target, or None if it can't be
+    /// reached
+    pub fn path_to(&self, target_idx: usize) -> Option<Vec<usize>> {
+        self.distances[target_idx]?;
+        let mut nodes_in_order = vec![target_idx];
+        let mut idx = target_idx;
+        while let Some(parent_idx) = self.parents[idx] {
+            nodes_in_order.push(parent_idx);
+            idx = parent_idx;
+        }
+        nodes_in_order.reverse();
+        return Some(nodes_in_order);
+    }
+
+    /// the shortest route from the source to the target, with its node names and leg weights
+    pub fn route_to(&self, graph: &Graph<W>, target_idx: usize) -> Result<Route<W>, DijkstraError> {
+        let nodes_in_order = self
+            .path_to(target_idx)
+            .ok_or_else(|| DijkstraError::disconnected(graph, self.source_idx, target_idx))?;
+        return Route::new(graph, nodes_in_order);
+    }
+
+    /// `children[node_idx]` are the nodes whose shortest route comes straight from that node,
+    /// closest first
+    pub fn children(&self) -> Vec<Vec<usize>> {
+        let mut children = vec![Vec::new(); self.parents.len()];
+        for (node_idx, parent) in self.parents.iter().enumerate() {
+            if let Some(parent_idx) = parent {
+                children[*parent_idx].push(node_idx);
+            }
+        }
+        for node_children in &mut children {
+            node_children.sort_by_key(|&node_idx| (self.distances[node_idx], node_idx));
+        }
+        return children;
+    }
+
+    /// the tree with each node on its own line, indented below its parent, i.e.
+    ///
+    /// ```text
+    /// London, dist 0
+    ///   York, dist 194
+    ///     Newcastle, dist 276
+    /// ```
+    pub fn to_indented_string(&self, graph: &Graph<W>) -> String {
+        let children = self.children();
+        let mut text = String::new();
+        // depth first, with the children pushed in reverse so the closest is printed first
+        let mut to_print = vec![(self.source_idx, 0)];
+        while let Some((node_idx, depth)) = to_print.pop() {
+            let dist =
+                self.distances[node_idx].expect("Expected every node in the tree to be reached");
+            text.push_str(&format!(
+                "{}{}, dist {}\n",
+                "  ".repeat(depth),
+                graph.graph_nodes[node_idx].node_name,
+                dist
+            ));
+            for &child_idx in children[node_idx].iter().rev() {
+                to_print.push((child_idx, depth + 1));
+            }
+        }
+        return text;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UK: &str = "8\nInverness\nGlasgow\nEdinburgh\nNewcastle\nManchester\nYork\nBirmingham\nLondon\n\n12\nInverness Glasgow 167\nInverness Edinburgh 158\nGlasgow Edinburgh 45\nGlasgow Newcastle 145\nGlasgow Manchester 214\nEdinburgh Newcastle 107\nNewcastle York 82\nManchester York 65\nManchester Birmingham 81\nYork Birmingham 129\nYork London 194\nBirmingham London 111\n\nLondon Inverness";
+
+    #[test]
+    fn tree_matches_dijkstra_for_every_target() {
+        let graph = Graph::new_from_string(UK).unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let tree = context.shortest_path_tree(&graph, 7);
+        assert_eq!(tree.parents[7], None);
+        for target_idx in 0..graph.number_of_nodes {
+            let (dist, nodes_in_order) = context.dijkstra(&graph, 7, target_idx).unwrap();
+            assert_eq!(tree.distances[target_idx], Some(dist));
+            assert_eq!(tree.path_to(target_idx), Some(nodes_in_order));
+        }
+        assert_eq!(
+            tree.route_to(&graph, 0).unwrap().to_string(),
+            "London->York->Newcastle->Edinburgh->Inverness, dist 541"
+        );
+    }
+    #[test]
+    fn unreachable_nodes_are_left_out() {
+        let graph = Graph::new_from_string("4\nA\nB\nC\nD\n\n2\nA -> B 1\nC D 2\n\nA B").unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let tree = context.shortest_path_tree(&graph, 1);
+        assert_eq!(tree.distances, vec![None, Some(0), None, None]);
+        assert_eq!(tree.path_to(1), Some(vec![1]));
+        assert_eq!(tree.path_to(0), None);
+        assert_eq!(
+            tree.route_to(&graph, 3),
+            Err(DijkstraError::Disconnected {
+                start_idx: 1,
+                end_idx: 3,
+                start_name: "B".to_string(),
+                end_name: "D".to_string()
+            })
+        );
+        assert_eq!(tree.to_indented_string(&graph), "B, dist 0\n");
+    }
+    #[test]
+    fn tree_with_negative_weights() {
+        let graph = Graph::<i64>::new_from_string_with_signed_weights(
+            "4\nA\nB\nC\nD\n\n4 directed\nA B 4\nA C 2\nB D -3\nC D 1\n\nA D",
+        )
+        .unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let tree = context
+            .shortest_path_tree_with_negative_weights(&graph, 0)
+            .unwrap();
+        assert_eq!(tree.distances, vec![Some(0), Some(4), Some(2), Some(1)]);
+        assert_eq!(tree.parents, vec![None, Some(0), Some(0), Some(1)]);
+        assert_eq!(
+            tree.to_indented_string(&graph),
+            "A, dist 0\n  C, dist 2\n  B, dist 4\n    D, dist 1\n"
+        );
+    }
+    #[test]
+    fn tree_as_indented_hierarchy() {
+        let graph = Graph::new_from_string(UK).unwrap();
+        let mut context = SearchContext::new(graph.number_of_nodes);
+        let tree = context.shortest_path_tree(&graph, 7);
+        assert_eq!(
+            tree.to_indented_string(&graph),
+            "London, dist 0\n  Birmingham, dist 111\n    Manchester, dist 192\n      Glasgow, dist 406\n  York, dist 194\n    Newcastle, dist 276\n      Edinburgh, dist 383\n        Inverness, dist 541\n"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use crate::construct_graph::Graph;
+use crate::error::DijkstraError;
+use crate::parse_input::Edge;
+use crate::weight::Weight;
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// The edges of minimum total weight that connect every node to every other node it can reach,
+/// i.e. the cheapest way to lay cable along the edges. A graph that is in more than one piece
+/// gets a tree for each piece. The direction of directed edges is ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanningForest<W = usize> {
+    /// the chosen edges, as they are in `graph.edges`
+    pub edges: Vec<Edge<W>>,
+    pub total_weight: W,
+    /// 1 if the graph is connected, and one more for every piece it is split into
+    pub number_of_trees: usize,
+}
+
+impl<W: Weight> SpanningForest<W> {
+    /// Prim's algorithm: grow each tree from its lowest indexed node, adding the lightest edge
+    /// that reaches a node not yet in the tree
+    pub fn prim(graph: &Graph<W>) -> Result<SpanningForest<W>, DijkstraError> {
+        // `incident_edges[node_idx]` are the positions in graph.edges of the edges at that node
+        let mut incident_edges: Vec<Vec<usize>> = vec![Vec::new(); graph.number_of_nodes];
+        for (edge_pos, edge) in graph.edges.iter().enumerate() {
+            if edge.index_first != edge.index_second {
+                incident_edges[edge.index_first].push(edge_pos);
+                incident_edges[edge.index_second].push(edge_pos);
+            }
+        }
+
+        let mut is_in_tree = vec![false; graph.number_of_nodes];
+        let mut chosen = Vec::new();
+        let mut number_of_trees = 0;
+        // the edges leaving the tree, lightest first, with ties broken by their position
+        let mut frontier: BinaryHeap<Reverse<(W, usize)>> = BinaryHeap::new();
+        for root_idx in 0..graph.number_of_nodes {
+            if is_in_tree[root_idx] {
+                continue;
+            }
+            number_of_trees += 1;
+            let mut newest_idx = root_idx;
+            loop {
+                is_in_tree[newest_idx] = true;
+                for &edge_pos in &incident_edges[newest_idx] {
+                    frontier.push(Reverse((graph.edges[edge_pos].weight, edge_pos)));
+                }
+                // edges whose ends have both joined the tree since they were pushed are skipped
+                let mut next = None;
+                while let Some(Reverse((_, edge_pos))) = frontier.pop() {
+                    let edge = &graph.edges[edge_pos];
+                    if !is_in_tree[edge.index_first] {
+                        next = Some((edge_pos, edge.index_first));
+                        break;
+                    }
+                    if !is_in_tree[edge.index_second] {
+                        next = Some((edge_pos, edge.index_second));
+                        break;
+                    }
+                }
+                match next {
+                    Some((edge_pos, node_idx)) => {
+                        chosen.push(edge_pos);
+                        newest_idx = node_idx;
+                    }
+                    None => break,
+                }
+            }
+        }
+        return SpanningForest::new_from_edge_positions(graph, chosen, number_of_trees);
+    }
+
+    /// Kruskal's algorithm: go through the edges from lightest to heaviest, keeping each edge
+    /// that joins two trees that aren't yet connected
+    pub fn kruskal(graph: &Graph<W>) -> Result<SpanningForest<W>, DijkstraError> {
+        let mut edge_positions: Vec<usize> = (0..graph.edges.len()).collect();
+        edge_positions.sort_by_key(|&edge_pos| (graph.edges[edge_pos].weight, edge_pos));
+
+        let mut trees = DisjointSets::new(graph.number_of_nodes);
+        let mut chosen = Vec::new();
+        for edge_pos in edge_positions {
+            let edge = &graph.edges[edge_pos];
+            if trees.union(edge.index_first, edge.index_second) {
+                chosen.push(edge_pos);
+            }
+        }
+        return SpanningForest::new_from_edge_positions(graph, chosen, trees.number_of_sets);
+    }
+
+    fn new_from_edge_positions(
+        graph: &Graph<W>,
+        edge_positions: Vec<usize>,
+        number_of_trees: usize,
+    ) -> Result<SpanningForest<W>, DijkstraError> {
+        let edges: Vec<Edge<W>> = edge_positions
+            .into_iter()
+            .map(|edge_pos| graph.edges[edge_pos])
+            .collect();
+        let total_weight = edges
+            .iter()
+            .try_fold(W::zero(), |total, edge| total.checked_add(edge.weight))
+            .ok_or(DijkstraError::TotalWeightOverflow)?;
+        return Ok(SpanningForest {
+            edges,
+            total_weight,
+            number_of_trees,
+        });
+    }
+}
+
+/// Union-find over the node indexes, to tell whether two nodes are already in the same tree
+struct DisjointSets {
+    /// `parents[node_idx]` leads towards the root of the node's set, a root is its own parent
+    parents: Vec<usize>,
+    /// `sizes[root_idx]` is the number of nodes in the set, only kept up to date for roots
+    sizes: Vec<usize>,
+    number_of_sets: usize,
+}
+
+impl DisjointSets {
+    fn new(number_of_nodes: usize) -> DisjointSets {
+        return DisjointSets {
+            parents: (0..number_of_nodes).collect(),
+            sizes: vec![1; number_of_nodes],
+            number_of_sets: number_of_nodes,
+        };
+    }
+
+    /// the root of the node's set, pointing each node passed on the way at its grandparent so
+    /// that later finds are shorter
+    fn find(&mut self, mut node_idx: usize) -> usize {
+        while self.parents[node_idx] != node_idx {
+            let grandparent_idx = self.parents[self.parents[node_idx]];
+            self.parents[node_idx] = grandparent_idx;
+            node_idx = grandparent_idx;
+        }
+        return node_idx;
+    }
+
+    /// join the sets of the two nodes, attaching the smaller set to the larger.
+    /// Returns false if they were already in the same set.
+    fn union(&mut self, a_idx: usize, b_idx: usize) -> bool {
+        let (a_root, b_root) = (self.find(a_idx), self.find(b_idx));
+        if a_root == b_root {
+            return false;
+        }
+        let (larger, smaller) = if self.sizes[a_root] >= self.sizes[b_root] {
+            (a_root, b_root)
+        } else {
+            (b_root, a_root)
+        };
+        self.parents[smaller] = larger;
+        self.sizes[larger] += self.sizes[smaller];
+        self.number_of_sets -= 1;
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_graph::RandomGraphs;
+
+    use std::fs;
+
+    #[test]
+    fn prim_and_kruskal_choose_the_cheapest_edges() {
+        let graph = Graph::new_from_string(
+            "4\nA\nB\nC\nD\n\n6\nA B 1\nB C 2\nA C 3\nC D 4\nB D 5\nD D 0\n\nA D",
+        )
+        .unwrap();
+        for forest in [
+            SpanningForest::prim(&graph).unwrap(),
+            SpanningForest::kruskal(&graph).unwrap(),
+        ] {
+            let mut edges = forest.edges.clone();
+            edges.sort_by_key(|edge| edge.weight);
+            assert_eq!(
+                edges,
+                vec![Edge::new(0, 1, 1), Edge::new(1, 2, 2), Edge::new(2, 3, 4)]
+            );
+            assert_eq!(forest.total_weight, 7);
+            assert_eq!(forest.number_of_trees, 1);
+        }
+    }
+    #[test]
+    fn disconnected_graph_gets_a_forest() {
+        let graph = Graph::new_from_string(
+            "6\nA\nB\nC\nD\nE\nF\n\n4\nA -> B 3\nB C 1\nC A 2\nD E 5\n\nA B",
+        )
+        .unwrap();
+        for forest in [
+            SpanningForest::prim(&graph).unwrap(),
+            SpanningForest::kruskal(&graph).unwrap(),
+        ] {
+            // the directed edge A -> B is dropped for being the heaviest, not for its direction
+            assert_eq!(forest.total_weight, 8);
+            assert_eq!(forest.edges.len(), 3);
+            assert_eq!(forest.number_of_trees, 3);
+        }
+        let empty = Graph::<usize>::new(Vec::new(), Vec::new()).unwrap();
+        assert_eq!(
+            SpanningForest::kruskal(&empty),
+            Ok(SpanningForest {
+                edges: Vec::new(),
+                total_weight: 0,
+                number_of_trees: 0
+            })
+        );
+    }
+    #[test]
+    fn prim_matches_kruskal() {
+        let contents = fs::read_to_string("src/test/uk.txt").unwrap();
+        let graph = Graph::new_from_string(&contents).unwrap();
+        let prim = SpanningForest::prim(&graph).unwrap();
+        assert_eq!(
+            prim.total_weight,
+            SpanningForest::kruskal(&graph).unwrap().total_weight
+        );
+        assert_eq!(prim.edges.len(), graph.number_of_nodes - 1);
+
+        // random graphs, with repeated weights so that there are ties to break
+        let mut random = RandomGraphs::new(7);
+        for _ in 0..20 {
+            let (number_of_nodes, number_of_edges) = (1 + random.next(30), random.next(60));
+            let graph = random.graph(
+                number_of_nodes,
+                number_of_edges,
+                |random, from_idx, to_idx| {
+                    return Some(Edge::new(from_idx, to_idx, random.next(10)));
+                },
+            );
+            let prim = SpanningForest::prim(&graph).unwrap();
+            let kruskal = SpanningForest::kruskal(&graph).unwrap();
+            assert_eq!(prim.total_weight, kruskal.total_weight);
+            assert_eq!(prim.number_of_trees, kruskal.number_of_trees);
+            assert_eq!(
+                prim.edges.len() + prim.number_of_trees,
+                graph.number_of_nodes
+            );
+        }
+    }
+    #[test]
+    fn total_weight_overflow_is_an_error() {
+        let graph =
+            Graph::<u8>::new_from_string_with_weights("3\nA\nB\nC\n\n2\nA B 200\nB C 100\n\nA C")
+                .unwrap();
+        assert_eq!(
+            SpanningForest::prim(&graph),
+            Err(DijkstraError::TotalWeightOverflow)
+        );
+        assert_eq!(
+            SpanningForest::kruskal(&graph),
+            Err(DijkstraError::TotalWeightOverflow)
+        );
+    }
+}
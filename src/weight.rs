@@ -0,0 +1,279 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The type of the edge weights and distances in a graph.
+/// Implemented for the integer types, and for `TotalF64` for fractional weights.
+pub trait Weight:
+    Copy + Ord + Add<Output = Self> + Sum + fmt::Debug + fmt::Display + Send + Sync + 'static
+{
+    fn zero() -> Self;
+
+    /// larger than any distance that can be travelled
+    fn infinity() -> Self;
+
+    /// None if the sum is too large to be represented
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    /// None if the difference can't be represented, i.e. is negative for an unsigned type
+    fn checked_sub(self, other: Self) -> Option<Self>;
+
+    /// read a weight from the text format. Negative weights are returned as they are, it is up
+    /// to the caller whether they are allowed.
+    fn parse_weight(text: &str) -> Result<Self, WeightParseError>;
+
+    /// convert a distance, i.e. a straight-line distance, rounding down so that it is never
+    /// larger than the distance it came from
+    fn from_f64_rounded_down(value: f64) -> Self;
+}
+
+/// Why a weight in the text format could not be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightParseError {
+    /// integer weights can't have a decimal point
+    NotAnInteger,
+    NotANumber,
+    NaN,
+    Infinite,
+    /// a negative integer, for a weight type that can't hold one
+    Negative,
+}
+
+impl fmt::Display for WeightParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeightParseError::NotAnInteger => return write!(f, "should be an integer"),
+            WeightParseError::NotANumber => return write!(f, "should be a number"),
+            WeightParseError::NaN => return write!(f, "can't be NaN"),
+            WeightParseError::Infinite => return write!(f, "should be finite"),
+            WeightParseError::Negative => return write!(f, "can't be negative"),
+        }
+    }
+}
+
+macro_rules! impl_weight_for_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Weight for $t {
+                fn zero() -> Self {
+                    return 0;
+                }
+
+                fn infinity() -> Self {
+                    return <$t>::MAX;
+                }
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    return <$t>::checked_add(self, other);
+                }
+
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    return <$t>::checked_sub(self, other);
+                }
+
+                fn parse_weight(text: &str) -> Result<Self, WeightParseError> {
+                    return text.parse::<$t>().map_err(|_| {
+                        match text.parse::<i128>() {
+                            Ok(value) if value < 0 => return WeightParseError::Negative,
+                            _ => return WeightParseError::NotAnInteger,
+                        }
+                    });
+                }
+
+                fn from_f64_rounded_down(value: f64) -> Self {
+                    // `as` saturates at the bounds of the integer type
+                    return value.floor() as $t;
+                }
+            }
+        )*
+    };
+}
+
+impl_weight_for_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// A float weight that is never NaN, so that weights can be totally ordered.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TotalF64(f64);
+
+impl TotalF64 {
+    /// None if the value is NaN
+    pub fn new(value: f64) -> Option<TotalF64> {
+        if value.is_nan() {
+            return None;
+        }
+        return Some(TotalF64(value));
+    }
+
+    pub fn value(self) -> f64 {
+        return self.0;
+    }
+}
+
+impl Serialize for TotalF64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_f64(self.0);
+    }
+}
+
+impl<'de> Deserialize<'de> for TotalF64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<TotalF64, D::Error> {
+        let value = f64::deserialize(deserializer)?;
+        return TotalF64::new(value).ok_or_else(|| de::Error::custom("the weight is NaN"));
+    }
+}
+
+/// equality agrees with the ordering, so `-0.0` and `0.0` are different weights
+impl PartialEq for TotalF64 {
+    fn eq(&self, other: &Self) -> bool {
+        return self.cmp(other) == Ordering::Equal;
+    }
+}
+
+impl Eq for TotalF64 {}
+
+impl PartialOrd for TotalF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for TotalF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.0.total_cmp(&other.0);
+    }
+}
+
+impl Add for TotalF64 {
+    type Output = TotalF64;
+
+    fn add(self, other: TotalF64) -> TotalF64 {
+        return TotalF64(self.0 + other.0);
+    }
+}
+
+impl Sum for TotalF64 {
+    fn sum<I: Iterator<Item = TotalF64>>(iter: I) -> TotalF64 {
+        return iter.fold(TotalF64::zero(), |total, weight| total + weight);
+    }
+}
+
+impl fmt::Display for TotalF64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.0);
+    }
+}
+
+impl Weight for TotalF64 {
+    fn zero() -> Self {
+        return TotalF64(0.0);
+    }
+
+    fn infinity() -> Self {
+        return TotalF64(f64::INFINITY);
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        let sum = self.0 + other.0;
+        if sum.is_finite() {
+            return Some(TotalF64(sum));
+        }
+        return None;
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        let difference = self.0 - other.0;
+        if difference.is_finite() {
+            return Some(TotalF64(difference));
+        }
+        return None;
+    }
+
+    fn parse_weight(text: &str) -> Result<Self, WeightParseError> {
+        let value = text
+            .parse::<f64>()
+            .map_err(|_| WeightParseError::NotANumber)?;
+        if value.is_nan() {
+            return Err(WeightParseError::NaN);
+        }
+        if value.is_infinite() {
+            return Err(WeightParseError::Infinite);
+        }
+        return Ok(TotalF64(value));
+    }
+
+    fn from_f64_rounded_down(value: f64) -> Self {
+        return TotalF64(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_weights() {
+        assert_eq!(usize::parse_weight("12"), Ok(12));
+        assert_eq!(
+            usize::parse_weight("1.5"),
+            Err(WeightParseError::NotAnInteger)
+        );
+        assert_eq!(i64::parse_weight("-3"), Ok(-3));
+        assert_eq!(usize::parse_weight("-3"), Err(WeightParseError::Negative));
+        assert_eq!(TotalF64::parse_weight("1.5"), Ok(TotalF64(1.5)));
+        assert_eq!(TotalF64::parse_weight("-0.5"), Ok(TotalF64(-0.5)));
+        assert_eq!(TotalF64::parse_weight("NaN"), Err(WeightParseError::NaN));
+        assert_eq!(
+            TotalF64::parse_weight("inf"),
+            Err(WeightParseError::Infinite)
+        );
+        assert_eq!(
+            TotalF64::parse_weight("far"),
+            Err(WeightParseError::NotANumber)
+        );
+    }
+    #[test]
+    fn test_total_f64_ordering() {
+        let mut weights = vec![TotalF64(2.5), TotalF64(0.1), TotalF64::infinity()];
+        weights.sort();
+        assert_eq!(
+            weights,
+            vec![TotalF64(0.1), TotalF64(2.5), TotalF64(f64::INFINITY)]
+        );
+        assert_eq!(TotalF64::new(f64::NAN), None);
+        assert_eq!(
+            vec![TotalF64(0.1), TotalF64(0.2)]
+                .into_iter()
+                .sum::<TotalF64>(),
+            TotalF64(0.1 + 0.2)
+        );
+    }
+    #[test]
+    fn test_checked_add() {
+        assert_eq!(Weight::checked_add(usize::MAX - 1, 1), Some(usize::MAX));
+        assert_eq!(Weight::checked_add(usize::MAX, 1), None);
+        assert_eq!(Weight::checked_add(-3_i32, 2), Some(-1));
+        assert_eq!(
+            Weight::checked_add(TotalF64(f64::MAX), TotalF64(f64::MAX)),
+            None
+        );
+        assert_eq!(
+            Weight::checked_add(TotalF64(0.5), TotalF64(0.25)),
+            Some(TotalF64(0.75))
+        );
+        assert_eq!(Weight::checked_sub(3_usize, 4), None);
+        assert_eq!(Weight::checked_sub(3_i64, 4), Some(-1));
+        assert_eq!(
+            Weight::checked_sub(TotalF64(f64::MIN), TotalF64(f64::MAX)),
+            None
+        );
+    }
+    #[test]
+    fn test_rounding_down() {
+        assert_eq!(usize::from_f64_rounded_down(4.99), 4);
+        assert_eq!(u8::from_f64_rounded_down(1000.0), 255);
+        assert_eq!(TotalF64::from_f64_rounded_down(4.99), TotalF64(4.99));
+    }
+}